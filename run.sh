@@ -1,8 +1,36 @@
 echo "1. Building the Rust application..."
 cargo build
 
+# The port the server will listen on, in the same order the application reads it:
+# --port, GO_SERVICE_PORT, "port" in the config file (--config, GO_SERVICE_CONFIG or
+# go_service.json), then the default 80
+port=""
+config="${GO_SERVICE_CONFIG:-go_service.json}"
+prev=""
+for arg in "$@"; do
+    case "$prev" in
+        --port) port="$arg" ;;
+        --config) config="$arg" ;;
+    esac
+    case "$arg" in
+        --port=*) port="${arg#--port=}" ;;
+        --config=*) config="${arg#--config=}" ;;
+    esac
+    prev="$arg"
+done
+if [ -z "$port" ]; then
+    port="$GO_SERVICE_PORT"
+fi
+if [ -z "$port" ] && [ -f "$config" ]; then
+    port=$(sed -n 's/.*"port"[[:space:]]*:[[:space:]]*"\{0,1\}\([0-9][0-9]*\).*/\1/p' "$config" | head -n 1)
+fi
+port="${port:-80}"
+
+# Ports below 1024 need the capability; on e.g. 8080 it can be skipped
+if [ "$port" -lt 1024 ]; then
 echo "2. Setting network capability on new binary..."
 sudo setcap 'cap_net_bind_service=+ep' target/debug/go_service
+fi
 
 echo "3. Running the application directly..."
-target/debug/go_service
\ No newline at end of file
+target/debug/go_service "$@"
@@ -0,0 +1,359 @@
+use actix_web::{
+    delete, get,
+    http::{header, StatusCode},
+    post, put,
+    web::{Bytes, Data, Path, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::elements::shortcut::{
+    create_shortcut, find_shortcut, remove_shortcut, update_shortcut, ShortcutError, ShortcutInput,
+};
+use crate::hidden;
+use crate::history::actor;
+use crate::resolve::{encode_component, ResolveError, Resolver};
+use crate::variables::VariableContext;
+
+// A shortcut as returned by the API, with the collection it lives in
+#[derive(Serialize)]
+struct ApiShortcut<'a> {
+    key: &'a str,
+    collection: &'a str,
+    hidden: bool,
+    read_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'a str>,
+    #[serde(flatten)]
+    shortcut: &'a Shortcut,
+}
+
+// Request body for POST and PUT. On PUT a different `key` renames the shortcut.
+#[derive(Deserialize)]
+struct ApiShortcutBody {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    owner: String,
+    #[serde(default)]
+    notes: String,
+    // Unix seconds, 0 for no limit; left out, the current value is kept
+    #[serde(default)]
+    active_from: Option<u64>,
+    #[serde(default)]
+    expires_at: Option<u64>,
+    // Only used on POST; defaults to the first visible, writable collection
+    #[serde(default)]
+    collection: Option<String>,
+}
+
+impl ApiShortcutBody {
+    fn input(&self) -> ShortcutInput {
+        let mut input = ShortcutInput::new(&self.url, &self.description, &self.tags, &self.owner, &self.notes);
+        input.active_from = self.active_from;
+        input.expires_at = self.expires_at;
+        input
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    pub collection: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ResolveQuery {
+    #[serde(default)]
+    pub path: String,
+}
+
+// Error body shared by every API response: {"error": {"code", "message", "field"?}}
+fn api_error(status: StatusCode, code: &str, message: &str, field: Option<&str>) -> HttpResponse {
+    let mut error = json!({ "code": code, "message": message });
+    if let Some(field) = field {
+        error["field"] = json!(field);
+    }
+    HttpResponse::build(status).json(json!({ "error": error }))
+}
+
+fn shortcut_error(e: ShortcutError) -> HttpResponse {
+    let (code, field) = match &e {
+        ShortcutError::Invalid { field, .. } => ("invalid", Some(*field)),
+        ShortcutError::NotFound(_) => ("not_found", None),
+        ShortcutError::Conflict(_) => ("conflict", None),
+        ShortcutError::ReadOnly(_) => ("read_only", None),
+        ShortcutError::SaveFailed => ("save_failed", None),
+    };
+    api_error(e.status(), code, &e.message(), field)
+}
+
+fn not_found(key: &str) -> HttpResponse {
+    api_error(StatusCode::NOT_FOUND, "not_found", &format!("Shortcut '{}' does not exist.", key), None)
+}
+
+fn invalid_json(e: serde_json::Error) -> HttpResponse {
+    api_error(StatusCode::BAD_REQUEST, "invalid_json", &format!("Invalid JSON body: {}", e), None)
+}
+
+fn api_shortcut<'a>(key: &'a str, collection: &'a ShortcutCollection, shortcut: &'a Shortcut) -> ApiShortcut<'a> {
+    ApiShortcut {
+        key,
+        collection: &collection.name,
+        hidden: collection.hidden,
+        read_only: collection.directory,
+        source: collection.sources.get(key).map(String::as_str),
+        shortcut,
+    }
+}
+
+// Strong validator for one shortcut: changes whenever the alias, its collection or any field does
+fn etag(key: &str, collection: &ShortcutCollection, shortcut: &Shortcut) -> String {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    collection.name.hash(&mut hasher);
+    serde_json::to_string(shortcut).unwrap_or_default().hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+// Response for a single shortcut, with its ETag
+fn shortcut_response(status: StatusCode, collections: &[ShortcutCollection], key: &str) -> HttpResponse {
+    match find_shortcut(collections, key) {
+        Some((collection, shortcut)) => HttpResponse::build(status)
+            .insert_header((header::ETAG, etag(key, collection, shortcut)))
+            .json(api_shortcut(key, collection, shortcut)),
+        None => not_found(key),
+    }
+}
+
+fn header_value(req: &HttpRequest, name: header::HeaderName) -> Option<&str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+fn etag_matches(header: &str, current: &str) -> bool {
+    header.split(',').map(str::trim).any(|t| t == "*" || t.trim_start_matches("W/") == current)
+}
+
+// Optimistic concurrency: with `If-Match`, the change only goes through if the shortcut is
+// still the version the client last saw. Returns the rejection, if any.
+fn check_if_match(req: &HttpRequest, collections: &[ShortcutCollection], key: &str) -> Option<HttpResponse> {
+    let expected = header_value(req, header::IF_MATCH)?;
+    let current = find_shortcut(collections, key).map(|(c, s)| etag(key, c, s));
+    match current {
+        Some(current) if etag_matches(expected, &current) => None,
+        Some(_) => Some(api_error(
+            StatusCode::PRECONDITION_FAILED,
+            "precondition_failed",
+            &format!("Shortcut '{}' was changed by someone else; fetch it again and retry.", key),
+            None,
+        )),
+        None => Some(not_found(key)),
+    }
+}
+
+// Hidden shortcuts are only visible to sessions that unlocked them on /hidden
+fn hidden_visible(req: &HttpRequest, state: &AppState, collection: &ShortcutCollection) -> bool {
+    !collection.hidden || hidden::is_unlocked(req, state)
+}
+
+// Changes to a hidden shortcut need the same unlocked session as the /hidden page
+fn guard_hidden(req: &HttpRequest, state: &AppState, collections: &[ShortcutCollection], key: &str) -> Option<HttpResponse> {
+    hidden::guard_hidden(req, state, collections, key).map(|_| {
+        api_error(
+            StatusCode::FORBIDDEN,
+            "locked",
+            "This shortcut is hidden; unlock the hidden shortcuts on /hidden first.",
+            None,
+        )
+    })
+}
+
+// Handler for GET /api/shortcuts
+// Every shortcut in resolution order (collection priority, then key). `?collection=` and
+// `?tag=` narrow the list.
+#[get("/api/shortcuts")]
+pub async fn api_list(req: HttpRequest, query: Query<ListQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+
+    let mut list = Vec::new();
+    for collection in collections.iter() {
+        if !hidden_visible(&req, &state, collection) {
+            continue;
+        }
+        if query.collection.as_deref().is_some_and(|name| name != collection.name) {
+            continue;
+        }
+        let mut keys: Vec<&String> = collection.shortcuts.keys().collect();
+        keys.sort();
+        for key in keys {
+            let shortcut = &collection.shortcuts[key];
+            if let Some(tag) = &query.tag
+                && !shortcut.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                continue;
+            }
+            list.push(api_shortcut(key, collection, shortcut));
+        }
+    }
+
+    HttpResponse::Ok().json(list)
+}
+
+// Handler for GET /api/shortcuts/{key}
+#[get("/api/shortcuts/{key:.+}")]
+pub async fn api_get(req: HttpRequest, key: Path<String>, state: Data<Arc<AppState>>) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+
+    // A locked hidden shortcut isn't there as far as the API is concerned
+    if find_shortcut(&collections, &key).is_some_and(|(c, _)| !hidden_visible(&req, &state, c)) {
+        return not_found(&key);
+    }
+    if let Some((collection, shortcut)) = find_shortcut(&collections, &key)
+        && let Some(cached) = header_value(&req, header::IF_NONE_MATCH)
+    {
+        let current = etag(&key, collection, shortcut);
+        if etag_matches(cached, &current) {
+            return HttpResponse::NotModified().insert_header((header::ETAG, current)).finish();
+        }
+    }
+
+    shortcut_response(StatusCode::OK, &collections, &key)
+}
+
+// Handler for POST /api/shortcuts
+#[post("/api/shortcuts")]
+pub async fn api_create(req: HttpRequest, body: Bytes, state: Data<Arc<AppState>>) -> impl Responder {
+    let body: ApiShortcutBody = match serde_json::from_slice(&body) {
+        Ok(b) => b,
+        Err(e) => return invalid_json(e),
+    };
+    let key = body.key.as_deref().unwrap_or("").trim().to_string();
+
+    let mut collections = state.collections.lock().unwrap();
+    let target = match body.collection.as_deref() {
+        Some(name) => collections.iter().position(|c| c.name == name),
+        None => collections.iter().position(|c| !c.hidden && !c.directory),
+    };
+    let Some(index) = target else {
+        return api_error(StatusCode::BAD_REQUEST, "invalid", "Unknown shortcut collection.", Some("collection"));
+    };
+    if !hidden_visible(&req, &state, &collections[index]) {
+        return api_error(
+            StatusCode::FORBIDDEN,
+            "locked",
+            "This collection is hidden; unlock the hidden shortcuts on /hidden first.",
+            Some("collection"),
+        );
+    }
+    if let Some(response) = guard_hidden(&req, &state, &collections, &key) {
+        return response;
+    }
+
+    // Unlike the add form, POST never replaces an existing shortcut; that is what PUT is for
+    if let Some((collection, _)) = find_shortcut(&collections, &key) {
+        return api_error(
+            StatusCode::CONFLICT,
+            "conflict",
+            &format!("Shortcut '{}' already exists in {}.", key, collection.name),
+            Some("key"),
+        );
+    }
+    if let Err(e) = create_shortcut(&state, &mut collections, index, &key, body.input(), &actor(&req)) {
+        return shortcut_error(e);
+    }
+
+    let mut response = shortcut_response(StatusCode::CREATED, &collections, &key);
+    response.headers_mut().insert(
+        header::LOCATION,
+        header::HeaderValue::from_str(&format!("/api/shortcuts/{}", encode_component(&key)))
+            .unwrap_or(header::HeaderValue::from_static("/api/shortcuts")),
+    );
+    response
+}
+
+// Handler for PUT /api/shortcuts/{key}
+// Replaces the URL and details; fields left out of the body are cleared.
+#[put("/api/shortcuts/{key:.+}")]
+pub async fn api_update(req: HttpRequest, key: Path<String>, body: Bytes, state: Data<Arc<AppState>>) -> impl Responder {
+    let body: ApiShortcutBody = match serde_json::from_slice(&body) {
+        Ok(b) => b,
+        Err(e) => return invalid_json(e),
+    };
+    let new_key = body.key.as_deref().map(str::trim).unwrap_or(&key).to_string();
+
+    let mut collections = state.collections.lock().unwrap();
+    for key in [key.as_str(), new_key.as_str()] {
+        if let Some(response) = guard_hidden(&req, &state, &collections, key) {
+            return response;
+        }
+    }
+    if let Some(response) = check_if_match(&req, &collections, &key) {
+        return response;
+    }
+    if let Err(e) = update_shortcut(&state, &mut collections, &key, &new_key, body.input(), &actor(&req)) {
+        return shortcut_error(e);
+    }
+
+    shortcut_response(StatusCode::OK, &collections, &new_key)
+}
+
+// Handler for DELETE /api/shortcuts/{key}
+#[delete("/api/shortcuts/{key:.+}")]
+pub async fn api_delete(req: HttpRequest, key: Path<String>, state: Data<Arc<AppState>>) -> impl Responder {
+    let mut collections = state.collections.lock().unwrap();
+    if let Some(response) = guard_hidden(&req, &state, &collections, &key) {
+        return response;
+    }
+    if let Some(response) = check_if_match(&req, &collections, &key) {
+        return response;
+    }
+    if let Err(e) = remove_shortcut(&mut collections, &key, &actor(&req)) {
+        return shortcut_error(e);
+    }
+
+    HttpResponse::NoContent().finish()
+}
+
+// Handler for GET /api/resolve?path=gh/org/repo?tab=issues
+// Where `go` would redirect the path, without redirecting or counting a hit.
+#[get("/api/resolve")]
+pub async fn api_resolve(req: HttpRequest, query: Query<ResolveQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+    let rules = state.rules.lock().unwrap();
+    let go_settings = state.go_settings.lock().unwrap();
+
+    let resolver = Resolver {
+        collections: &collections,
+        rules: &rules,
+        settings: &go_settings,
+        variables: VariableContext::from_request(&req),
+    };
+    match resolver.resolve_request(&query.path) {
+        Ok(resolved) if resolved.locked && !hidden::is_unlocked(&req, &state) => api_error(
+            StatusCode::FORBIDDEN,
+            "locked",
+            "This shortcut is locked; unlock it on /hidden first.",
+            None,
+        ),
+        Ok(resolved) => HttpResponse::Ok().json(json!({ "url": resolved.url, "alias": resolved.alias })),
+        Err(ResolveError::NotFound) => api_error(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            &format!("No shortcut matches '{}'.", query.path),
+            None,
+        ),
+        Err(e) => api_error(StatusCode::LOOP_DETECTED, "chain_error", &e.describe(), None),
+    }
+}
@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 use std::sync::Mutex;
+use std::time::SystemTime;
 use serde::{Serialize, Deserialize};
 
+use crate::health::LinkHealth;
+use crate::rules::CompiledRule;
 use crate::sql::DbConnection;
+use crate::stats::ShortcutStats;
 
 // NEW STRUCT: Note
 // This struct stores both the subject and the content of a saved note.
@@ -12,6 +16,115 @@ pub struct Note {
     pub content: String,
 }
 
+// A shortcut and the context that goes with it. Files may still store a bare
+// "alias": "url" string; see elements::shortcut::load_shortcuts.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Shortcut {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    // Unix seconds; 0 means unknown (e.g. shortcuts from the old format)
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64,
+    // Redirecting needs the hidden-shortcuts passphrase (see hidden.rs)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    // Unix seconds the shortcut starts and stops redirecting; 0 means no limit
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub active_from: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub expires_at: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl Shortcut {
+    pub fn new(url: &str) -> Self {
+        Shortcut { url: url.to_string(), ..Default::default() }
+    }
+
+    // True if nothing but the URL is set, so it can be saved in the simple format
+    pub fn is_plain(&self) -> bool {
+        self.description.is_empty()
+            && self.tags.is_empty()
+            && self.owner.is_empty()
+            && self.notes.is_empty()
+            && self.created_at == 0
+            && self.updated_at == 0
+            && !self.locked
+            && self.active_from == 0
+            && self.expires_at == 0
+    }
+
+    // True if `now` lies inside the shortcut's active window
+    pub fn is_active(&self, now: u64) -> bool {
+        now >= self.active_from && !self.is_expired(now)
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+// One entry of collections.json: a named shortcut file. `go` checks collections by
+// ascending priority (1 before 2); without a priority, the position in the list is used.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CollectionConfig {
+    pub name: String,
+    pub file: String,
+    // Hidden collections still resolve, but are left out of the home page table
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub priority: Option<i32>,
+    // `file` is a directory of JSON fragments, merged in filename order into a read-only collection
+    #[serde(default)]
+    pub directory: bool,
+}
+
+// A loaded collection: its configuration plus the shortcuts from its file
+pub struct ShortcutCollection {
+    pub name: String,
+    pub file: String,
+    pub hidden: bool,
+    pub priority: i32,
+    pub shortcuts: HashMap<String, Shortcut>,
+    // Fragment directory collections can't be changed from the UI
+    pub directory: bool,
+    // Fragment file each alias came from (directory collections only)
+    pub sources: HashMap<String, String>,
+    // Modification time of `file` when it was last read, checked by the reload watcher
+    pub modified: Option<SystemTime>,
+    // Why the file on disk couldn't be loaded; `shortcuts` keeps the last good version
+    pub load_error: Option<String>,
+}
+
+impl ShortcutCollection {
+    pub fn new(config: &CollectionConfig, priority: i32, shortcuts: HashMap<String, Shortcut>) -> Self {
+        ShortcutCollection {
+            name: config.name.clone(),
+            file: config.file.clone(),
+            hidden: config.hidden,
+            priority,
+            shortcuts,
+            directory: config.directory,
+            sources: HashMap::new(),
+            modified: None,
+            load_error: None,
+        }
+    }
+}
+
 // Define the structure for a theme, which consists of CSS color variables
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Theme {
@@ -26,10 +139,68 @@ pub struct Theme {
     pub border_color: String,  // e.g., #444 (Borders/Dividers)
 }
 
+// Settings that control how shortcuts are resolved, persisted to go_settings.json
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GoSettings {
+    // Where templated shortcuts redirect when an argument is missing.
+    // Empty means "the template up to its first placeholder".
+    #[serde(default)]
+    pub template_fallback_url: String,
+    // Where expired or not yet active shortcuts redirect.
+    // Empty means they are treated as missing (the 404 page with suggestions).
+    #[serde(default)]
+    pub inactive_fallback_url: String,
+    // Seconds between background link health checks; 0 turns them off
+    #[serde(default = "default_health_interval")]
+    pub health_check_interval_secs: u64,
+    // How many links are checked at the same time
+    #[serde(default = "default_health_concurrency")]
+    pub health_check_concurrency: usize,
+    // Minimum gap between two requests to the same host
+    #[serde(default = "default_health_host_delay")]
+    pub health_check_host_delay_ms: u64,
+    #[serde(default = "default_health_timeout")]
+    pub health_check_timeout_secs: u64,
+}
+
+fn default_health_interval() -> u64 {
+    6 * 60 * 60
+}
+
+fn default_health_concurrency() -> usize {
+    4
+}
+
+fn default_health_host_delay() -> u64 {
+    1000
+}
+
+fn default_health_timeout() -> u64 {
+    10
+}
+
+impl Default for GoSettings {
+    fn default() -> Self {
+        GoSettings {
+            template_fallback_url: String::new(),
+            inactive_fallback_url: String::new(),
+            health_check_interval_secs: default_health_interval(),
+            health_check_concurrency: default_health_concurrency(),
+            health_check_host_delay_ms: default_health_host_delay(),
+            health_check_timeout_secs: default_health_timeout(),
+        }
+    }
+}
+
 pub struct AppState {
-    pub shortcuts: Mutex<HashMap<String, String>>,
-    pub hidden_shortcuts: Mutex<HashMap<String, String>>,
-    pub work_shortcuts: Mutex<HashMap<String, String>>,
+    // All shortcut collections, in resolution order
+    pub collections: Mutex<Vec<ShortcutCollection>>,
+    pub go_settings: Mutex<GoSettings>,
+    pub rules: Mutex<Vec<CompiledRule>>, // Rule shortcuts, in evaluation order
+    pub stats: Mutex<HashMap<String, ShortcutStats>>, // Per-alias usage, keyed by shortcut
+    pub health: Mutex<HashMap<String, LinkHealth>>, // Latest link check, keyed by destination URL
+    pub sessions: Mutex<HashMap<String, u64>>, // Login session id -> expiry (unix seconds)
+    pub unlocked: Mutex<HashMap<String, u64>>, // Hidden-shortcuts unlock session id -> expiry
     // UPDATED: Use Vec<Note> instead of Vec<String>
     pub notes: Mutex<Vec<Note>>,
 
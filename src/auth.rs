@@ -0,0 +1,252 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    cookie::{time::Duration as CookieDuration, Cookie, SameSite},
+    dev::{ServiceRequest, ServiceResponse},
+    get,
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    post,
+    web::{Data, Form, Query},
+    Error, HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+use htmlescape::encode_minimal;
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::app_state::AppState;
+use crate::base_page::render_base_page;
+use crate::clock::{current_ts, SECONDS_IN_DAY};
+use crate::config::config;
+use crate::resolve::encode_component;
+
+pub static SESSION_COOKIE: &str = "go_session";
+
+// Route pattern of the catch-all `go` handler (see not_found.rs)
+const GO_ROUTE: &str = "/{tail:.*}";
+
+// Marks a request that passed the session or token check
+#[derive(Clone, Copy)]
+struct Authenticated;
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginForm {
+    pub password: String,
+    #[serde(default)]
+    pub next: String,
+}
+
+// Middleware wrapping every route. With a password or token configured, requests need a
+// session cookie (from /login) or an `Authorization: Bearer` token. Pages redirect to the
+// login form; the API and other requests get a 401.
+pub async fn require_auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if !config().auth_enabled() {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+    if has_valid_credentials(&req) {
+        req.extensions_mut().insert(Authenticated);
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+    if is_public(&req) {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+
+    let response = unauthorized(req.request());
+    Ok(req.into_response(response).map_into_right_body())
+}
+
+// True when authentication is off or the request carried valid credentials. Public routes
+// use it to leave out anything that lists shortcuts.
+pub fn is_authenticated(req: &HttpRequest) -> bool {
+    !config().auth_enabled() || req.extensions().get::<Authenticated>().is_some()
+}
+
+fn has_valid_credentials(req: &ServiceRequest) -> bool {
+    if has_valid_token(req.request()) {
+        return true;
+    }
+    match (req.cookie(SESSION_COOKIE), req.app_data::<Data<Arc<AppState>>>()) {
+        (Some(cookie), Some(state)) => session_valid(&state.sessions, cookie.value()),
+        _ => false,
+    }
+}
+
+// True when the request carries the configured API token (scripts, the CLI)
+pub fn has_valid_token(req: &HttpRequest) -> bool {
+    let token = &config().auth_token;
+    !token.is_empty() && bearer_token(req).is_some_and(|t| constant_time_eq(t, token))
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    value.strip_prefix("Bearer ").map(str::trim)
+}
+
+// Compares without stopping at the first difference, so timing doesn't leak the secret
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// True if `id` is in the session map and hasn't expired; drops expired entries on the way
+pub fn session_valid(sessions: &Mutex<HashMap<String, u64>>, id: &str) -> bool {
+    let now = current_ts();
+    let mut sessions = sessions.lock().unwrap();
+    sessions.retain(|_, expires| *expires > now);
+    sessions.contains_key(id)
+}
+
+// The login page, the stylesheet it needs and the search descriptor never need a login.
+// With `public_redirects`, neither do shortcut redirects.
+fn is_public(req: &ServiceRequest) -> bool {
+    let path = req.path();
+    if path == "/login" || path == "/opensearch.xml" || path.starts_with("/static/") {
+        return true;
+    }
+    config().public_redirects
+        && req.method() == Method::GET
+        && (path == "/search" || req.match_pattern().as_deref() == Some(GO_ROUTE))
+}
+
+fn unauthorized(req: &HttpRequest) -> HttpResponse {
+    let accepts_html = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/html"));
+
+    if req.method() == Method::GET && accepts_html {
+        let requested = req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        return HttpResponse::Found()
+            .append_header(("Location", format!("/login?next={}", encode_component(requested))))
+            .finish();
+    }
+
+    let mut response = HttpResponse::build(StatusCode::UNAUTHORIZED);
+    response.append_header((header::WWW_AUTHENTICATE, "Bearer"));
+    if req.path().starts_with("/api/") {
+        response.json(json!({ "error": { "code": "unauthorized", "message": "Authentication required." } }))
+    } else {
+        response.body("Authentication required.")
+    }
+}
+
+// Only same-site paths are followed after logging in (`//host` would leave the site)
+pub fn safe_next(next: &str) -> &str {
+    if next.starts_with('/') && !next.starts_with("//") && !next.starts_with("/\\") {
+        next
+    } else {
+        "/"
+    }
+}
+
+pub fn new_session_id() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn render_login(state: &AppState, next: &str, error: Option<&str>, status: StatusCode) -> HttpResponse {
+    let current_theme = state.current_theme.lock().unwrap();
+
+    let form = if config().auth_password.is_empty() {
+        "<p>Password login is turned off. Scripts can use the API token instead.</p>".to_string()
+    } else {
+        format!(
+            r#"
+        <form action="/login" method="POST">
+            <input type="hidden" name="next" value="{}">
+            <div class="settings-grid">
+                <div>
+                    <label for="password">Password:</label>
+                    <input type="password" id="password" name="password" autofocus required>
+                </div>
+            </div>
+            <button type="submit" class="form-submit-btn">Log In</button>
+        </form>"#,
+            encode_minimal(next)
+        )
+    };
+    let error = error
+        .map(|e| format!("<p class=\"login-error\">{}</p>", encode_minimal(e)))
+        .unwrap_or_default();
+
+    let content = format!(
+        r#"
+    <div class="settings-form">
+        <h1>Log In</h1>
+        {}
+        {}
+    </div>
+    "#,
+        error, form
+    );
+
+    HttpResponse::build(status)
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Log In", &content, &current_theme))
+}
+
+// Handler for GET /login
+#[get("/login")]
+pub async fn login_get(query: Query<LoginQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    render_login(&state, safe_next(query.next.as_deref().unwrap_or("/")), None, StatusCode::OK)
+}
+
+// Handler for POST /login
+#[post("/login")]
+pub async fn login_post(req: HttpRequest, form: Form<LoginForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let settings = config();
+    let next = safe_next(&form.next);
+
+    if settings.auth_password.is_empty() || !constant_time_eq(&form.password, &settings.auth_password) {
+        // Slow down password guessing
+        actix_web::rt::time::sleep(Duration::from_secs(1)).await;
+        return render_login(&state, next, Some("Wrong password."), StatusCode::UNAUTHORIZED);
+    }
+
+    let lifetime = settings.session_days.max(1) * SECONDS_IN_DAY;
+    let id = new_session_id();
+    state.sessions.lock().unwrap().insert(id.clone(), current_ts() + lifetime);
+
+    let cookie = Cookie::build(SESSION_COOKIE, id)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .secure(req.connection_info().scheme() == "https")
+        .max_age(CookieDuration::seconds(lifetime as i64))
+        .finish();
+
+    HttpResponse::Found()
+        .cookie(cookie)
+        .append_header(("Location", next))
+        .finish()
+}
+
+// Handler for POST /logout
+#[post("/logout")]
+pub async fn logout(req: HttpRequest, state: Data<Arc<AppState>>) -> impl Responder {
+    if let Some(cookie) = req.cookie(SESSION_COOKIE) {
+        state.sessions.lock().unwrap().remove(cookie.value());
+    }
+
+    let mut removal = Cookie::build(SESSION_COOKIE, "").path("/").finish();
+    removal.make_removal();
+
+    HttpResponse::Found()
+        .cookie(removal)
+        .append_header(("Location", "/login"))
+        .finish()
+}
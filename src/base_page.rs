@@ -1,4 +1,8 @@
-use crate::app_state::Theme; 
+use crate::app_state::{GoSettings, ShortcutCollection, Theme};
+use crate::config::config;
+use crate::csrf::csrf_script;
+use crate::variables;
+use htmlescape::encode_minimal;
 use std::collections::HashMap; 
 
 fn render_theme_variables(theme: &Theme) -> String {
@@ -24,7 +28,15 @@ fn render_theme_variables(theme: &Theme) -> String {
 }
 
 pub fn nav_bar_html() -> String {
-    r#"
+    // Only password logins have a session to end
+    let logout_button = if config().auth_password.is_empty() {
+        ""
+    } else {
+        r#"<form action="/logout" method="POST" class="logout-form"><button type="submit" class="nav-button">Log Out</button></form>"#
+    };
+
+    format!(
+        r#"
     <div class="tools">
       <div class="tool-buttons">
         <a href="/"><button class="nav-button">Home (Shortcuts)</button></a>
@@ -34,13 +46,17 @@ pub fn nav_bar_html() -> String {
         <a href="/calculator"><button class="nav-button">Calculator</button></a>
         <a href="/paint"><button class="nav-button">Paint</button></a>
         <a href="/request"><button class="nav-button">Requests</button></a>
+        <a href="/stats"><button class="nav-button">Stats</button></a>
       </div>
       <div class="right-buttons">
         <div id="optional-button-placeholder"></div>
         <a href="/settings"><button class="nav-button">Settings</button></a> 
+        {}
       </div>
     </div>
-    "#.to_string()
+    "#,
+        logout_button
+    )
 }
 
 pub fn render_base_page(title: &str, body_content: &str, current_theme: &Theme) -> String {
@@ -52,6 +68,8 @@ pub fn render_base_page(title: &str, body_content: &str, current_theme: &Theme)
     <title>{}</title>
     {} 
     <link rel="stylesheet" href="/static/style.css">
+    <link rel="search" type="application/opensearchdescription+xml" title="go" href="/opensearch.xml">
+    {}
   </head>
   <body>
     {}
@@ -60,6 +78,7 @@ pub fn render_base_page(title: &str, body_content: &str, current_theme: &Theme)
 </html>"#,
         title,
         render_theme_variables(current_theme),
+        csrf_script(),
         nav_bar_html(),
         body_content
     )
@@ -73,8 +92,28 @@ pub fn render_add_shortcut_button() -> String {
     "#.to_string()
 }
 
-pub fn render_add_shortcut_modal() -> String {
-    let modal_html = r#"
+pub fn render_add_shortcut_modal(collections: &[ShortcutCollection]) -> String {
+    // Offer every writable collection; hidden ones are labelled as such
+    let collection_options: String = collections
+        .iter()
+        .filter(|c| !c.directory)
+        .map(|c| {
+            format!(
+                "<option value=\"{0}\">{0}{1}</option>",
+                encode_minimal(&c.name),
+                if c.hidden { " (hidden)" } else { "" }
+            )
+        })
+        .collect();
+
+    // Every `{today}`-style variable (see variables.rs), e.g. for `https://docs.example/notes/{today}`
+    let variable_help = variables::list()
+        .iter()
+        .map(|(name, description)| format!("<code>{{{}}}</code> ({})", encode_minimal(name), encode_minimal(description)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let modal_html = format!(r#"
 <dialog id="addShortcutModal">
   <div class="modal-content">
     <span class="close-btn" id="closeModalBtn">&times;</span>
@@ -85,11 +124,32 @@ pub fn render_add_shortcut_modal() -> String {
 
       <label for="url">URL:</label>
       <input type="url" id="url" name="url" placeholder="e.g., https://github.com" required>
+      <small>Use <code>{{0}}</code>, <code>{{1}}</code> for path segments after the alias, or <code>{{name}}</code> for query parameters.</small>
+      <small>Variables filled in on every visit: {variable_help}</small>
 
-      <div style="margin-top: 15px;">
-        <input type="checkbox" id="hidden" name="hidden" value="true">
-        <label for="hidden" style="display: inline; font-weight: normal;">Hidden?</label>
-      </div>
+      <label for="description">Description:</label>
+      <input type="text" id="description" name="description" placeholder="What this link is for">
+
+      <label for="tags">Tags:</label>
+      <input type="text" id="tags" name="tags" placeholder="Comma separated, e.g., docs, team-a">
+
+      <label for="owner">Owner:</label>
+      <input type="text" id="owner" name="owner" placeholder="Who to ask about this link">
+
+      <label for="notes">Notes:</label>
+      <textarea id="notes" name="notes" rows="3"></textarea>
+
+      <label for="active_from">Active from (UTC, optional):</label>
+      <input type="datetime-local" id="active_from" name="active_from">
+
+      <label for="expires_at">Expires (UTC, optional):</label>
+      <input type="datetime-local" id="expires_at" name="expires_at">
+      <small>Outside this window the alias acts as if it didn't exist, or sends people to the inactive fallback from the settings.</small>
+
+      <label for="collection">Collection:</label>
+      <select id="collection" name="collection">
+        {collection_options}
+      </select>
 
       <div class="form-actions">
         <button type="submit" class="form-submit-btn">Save Shortcut</button>
@@ -97,7 +157,7 @@ pub fn render_add_shortcut_modal() -> String {
     </form>
   </div>
 </dialog>
-"#;
+"#, collection_options = collection_options, variable_help = variable_help);
 
     let modal_js = r#"
 <script>
@@ -136,6 +196,85 @@ pub fn render_add_shortcut_modal() -> String {
     format!("{}{}", modal_html, modal_js)
 }
 
+pub fn render_edit_shortcut_modal() -> String {
+    let modal_html = r#"
+<dialog id="editShortcutModal" class="shortcut-modal">
+  <div class="modal-content">
+    <span class="close-btn" id="closeEditModalBtn">&times;</span>
+    <h2>Edit Shortcut</h2>
+    <form action="/edit_shortcut" method="POST" class="modal-form">
+      <input type="hidden" id="edit_original_key" name="original_key">
+
+      <label for="edit_shortcut">Shortcut:</label>
+      <input type="text" id="edit_shortcut" name="shortcut" required>
+
+      <label for="edit_url">URL:</label>
+      <input type="url" id="edit_url" name="url" required>
+
+      <label for="edit_description">Description:</label>
+      <input type="text" id="edit_description" name="description">
+
+      <label for="edit_tags">Tags:</label>
+      <input type="text" id="edit_tags" name="tags">
+
+      <label for="edit_owner">Owner:</label>
+      <input type="text" id="edit_owner" name="owner">
+
+      <label for="edit_notes">Notes:</label>
+      <textarea id="edit_notes" name="notes" rows="3"></textarea>
+
+      <label for="edit_active_from">Active from (UTC, optional):</label>
+      <input type="datetime-local" id="edit_active_from" name="active_from">
+
+      <label for="edit_expires_at">Expires (UTC, optional):</label>
+      <input type="datetime-local" id="edit_expires_at" name="expires_at">
+
+      <div class="form-actions">
+        <button type="submit" class="form-submit-btn">Save Changes</button>
+      </div>
+    </form>
+  </div>
+</dialog>
+"#;
+
+    let modal_js = r#"
+<script>
+  document.addEventListener('DOMContentLoaded', () => {
+    const modal = document.getElementById('editShortcutModal');
+    if (!modal) return;
+
+    document.querySelectorAll('.edit-shortcut-btn').forEach(btn => {
+      btn.addEventListener('click', () => {
+        const d = btn.dataset;
+        document.getElementById('edit_original_key').value = d.key;
+        document.getElementById('edit_shortcut').value = d.key;
+        document.getElementById('edit_url').value = d.url;
+        document.getElementById('edit_description').value = d.description;
+        document.getElementById('edit_tags').value = d.tags;
+        document.getElementById('edit_owner').value = d.owner;
+        document.getElementById('edit_notes').value = d.notes;
+        document.getElementById('edit_active_from').value = d.activeFrom;
+        document.getElementById('edit_expires_at').value = d.expiresAt;
+        modal.showModal();
+      });
+    });
+
+    document.getElementById('closeEditModalBtn').onclick = () => modal.close();
+    modal.addEventListener('click', (e) => {
+      if (e.target.nodeName === 'DIALOG') {
+        const rect = e.target.getBoundingClientRect();
+        if (e.clientY < rect.top || e.clientY > rect.bottom ||
+            e.clientX < rect.left || e.clientX > rect.right) {
+          modal.close();
+        }
+      }
+    });
+  });
+</script>
+"#;
+
+    format!("{}{}", modal_html, modal_js)
+}
 
 pub fn render_settings_page(current_theme: &Theme, saved_themes: &HashMap<String, Theme>) -> String {
     let theme_options: String = saved_themes.keys()
@@ -248,4 +387,59 @@ pub fn render_settings_page(current_theme: &Theme, saved_themes: &HashMap<String
         border_color = current_theme.border_color,
         theme_options = theme_options
     )
-}
\ No newline at end of file
+}
+
+pub fn render_shortcut_settings(settings: &GoSettings) -> String {
+    format!(
+        r#"
+    <h1>Shortcut Settings</h1>
+    <form action="/save_shortcut_settings" method="POST" class="settings-form">
+        <div class="settings-grid">
+            <div>
+                <label for="template_fallback_url">Template Fallback URL:</label>
+                <input type="text" id="template_fallback_url" name="template_fallback_url" value="{fallback}" placeholder="Leave empty to use the template's base URL">
+            </div>
+        </div>
+        <p>Templated shortcuts such as <code>https://jira.example.com/browse/PROJ-{{0}}</code> redirect here when an argument is missing.</p>
+        <div class="settings-grid">
+            <div>
+                <label for="inactive_fallback_url">Inactive Shortcut Fallback URL:</label>
+                <input type="text" id="inactive_fallback_url" name="inactive_fallback_url" value="{inactive_fallback}" placeholder="Leave empty to show the not found page">
+            </div>
+        </div>
+        <p>Expired shortcuts, and ones whose start time hasn't come yet, redirect here.</p>
+        <h2>Link Health Checks</h2>
+        <div class="settings-grid">
+            <div>
+                <label for="health_check_interval_secs">Check every (seconds, 0 = off):</label>
+                <input type="number" min="0" id="health_check_interval_secs" name="health_check_interval_secs" value="{interval}">
+            </div>
+            <div>
+                <label for="health_check_concurrency">Links checked at once:</label>
+                <input type="number" min="1" id="health_check_concurrency" name="health_check_concurrency" value="{concurrency}">
+            </div>
+            <div>
+                <label for="health_check_host_delay_ms">Delay between requests to one host (ms):</label>
+                <input type="number" min="0" id="health_check_host_delay_ms" name="health_check_host_delay_ms" value="{host_delay}">
+            </div>
+            <div>
+                <label for="health_check_timeout_secs">Request timeout (seconds):</label>
+                <input type="number" min="1" id="health_check_timeout_secs" name="health_check_timeout_secs" value="{timeout}">
+            </div>
+        </div>
+        <p><a href="/shortcuts/import">Import or export shortcuts as browser bookmarks</a></p>
+        <p><a href="/hidden">Manage hidden shortcuts</a> (needs the hidden passphrase)</p>
+        <p><a href="/shortcuts/history">Shortcut change history</a> (revert changes or restore a collection)</p>
+        <div class="theme-action-buttons">
+            <button type="submit" class="form-submit-btn">Save Shortcut Settings</button>
+        </div>
+    </form>
+"#,
+        fallback = encode_minimal(&settings.template_fallback_url),
+        inactive_fallback = encode_minimal(&settings.inactive_fallback_url),
+        interval = settings.health_check_interval_secs,
+        concurrency = settings.health_check_concurrency,
+        host_delay = settings.health_check_host_delay_ms,
+        timeout = settings.health_check_timeout_secs,
+    )
+}
@@ -3,8 +3,7 @@ use std::{fs, io, sync::Arc, time::{SystemTime, UNIX_EPOCH}, collections::HashMa
 use serde::{Deserialize, Serialize};
 use crate::app_state::{AppState, Theme};
 use crate::base_page::render_base_page;
-
-const BOARD_FILE: &str = "board.json";
+use crate::config::files;
 
 // --- Data Structures ---
 
@@ -69,7 +68,7 @@ struct DeletePayload {
 // --- Logic ---
 
 fn load_board() -> BoardData {
-    if let Ok(data) = fs::read_to_string(BOARD_FILE) {
+    if let Ok(data) = fs::read_to_string(&files().board) {
         let mut board: BoardData = serde_json::from_str(&data).unwrap_or_else(|_| init_default_board());
         // Ensure columns are sorted by order field on load
         board.columns.sort_by_key(|c| c.order);
@@ -92,7 +91,7 @@ fn init_default_board() -> BoardData {
 
 fn save_board(data: &BoardData) -> io::Result<()> {
     let json = serde_json::to_string_pretty(data)?;
-    fs::write(BOARD_FILE, json)
+    fs::write(&files().board, json)
 }
 
 fn current_ts() -> u64 {
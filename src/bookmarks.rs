@@ -0,0 +1,667 @@
+use actix_web::{
+    get, post,
+    web::{Data, Form, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use htmlescape::{decode_html, encode_minimal};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::base_page::render_base_page;
+use crate::clock::current_ts;
+use crate::elements::shortcut::{check_chain, find_shortcut, save_collection};
+use crate::hidden;
+use crate::history::{self, actor, Change};
+use crate::resolve::host_of;
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ImportPreviewForm {
+    pub bookmarks: String,
+    pub collection: String,
+}
+
+// A bookmark read from an uploaded file, already turned into a shortcut
+pub struct ImportedBookmark {
+    pub alias: String,
+    pub shortcut: Shortcut,
+}
+
+// How an imported bookmark relates to the shortcuts we already have
+enum ImportStatus<'a> {
+    New,
+    Unchanged,
+    ReadOnly(&'a ShortcutCollection),
+    // Taken by a hidden shortcut while /hidden is locked, so its details aren't shown
+    Hidden,
+    Conflict(&'a ShortcutCollection, &'a Shortcut),
+}
+
+// Handler for GET /shortcuts/export?format=bookmarks
+// Writes every collection as a Netscape bookmark file, with a folder per collection and a
+// sub-folder per tag. Hidden collections are only included while /hidden is unlocked.
+#[get("/shortcuts/export")]
+pub async fn shortcuts_export(req: HttpRequest, query: Query<ExportQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    match query.format.as_deref().unwrap_or("bookmarks") {
+        "bookmarks" => {}
+        other => return HttpResponse::BadRequest().body(format!("Unsupported export format '{}'.", other)),
+    }
+
+    let collections = state.collections.lock().unwrap();
+    let unlocked = hidden::is_unlocked(&req, &state);
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .append_header(("Content-Disposition", "attachment; filename=\"shortcuts-bookmarks.html\""))
+        .body(render_bookmarks_file(collections.iter().filter(|c| unlocked || !c.hidden)))
+}
+
+pub fn render_bookmarks_file<'a>(collections: impl IntoIterator<Item = &'a ShortcutCollection>) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <!-- This is an automatically generated file. -->\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n",
+    );
+
+    for collection in collections {
+        let mut keys: Vec<&String> = collection.shortcuts.keys().collect();
+        keys.sort();
+
+        // A shortcut with several tags shows up in each tag's folder
+        let mut by_tag: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+        let mut untagged = Vec::new();
+        for key in &keys {
+            let tags = &collection.shortcuts[*key].tags;
+            if tags.is_empty() {
+                untagged.push(*key);
+            }
+            for tag in tags {
+                by_tag.entry(tag.as_str()).or_default().push(*key);
+            }
+        }
+
+        out.push_str(&format!("    <DT><H3>{}</H3>\n    <DL><p>\n", encode_minimal(&collection.name)));
+        for (tag, keys) in &by_tag {
+            out.push_str(&format!("        <DT><H3>{}</H3>\n        <DL><p>\n", encode_minimal(tag)));
+            for key in keys {
+                out.push_str(&bookmark_entry("            ", key, &collection.shortcuts[*key]));
+            }
+            out.push_str("        </DL><p>\n");
+        }
+        for key in untagged {
+            out.push_str(&bookmark_entry("        ", key, &collection.shortcuts[key]));
+        }
+        out.push_str("    </DL><p>\n");
+    }
+
+    out.push_str("</DL><p>\n");
+    out
+}
+
+// One `<DT><A>` line. The alias goes in SHORTCUTURL (the browser keyword), the description is
+// the title and notes go in the `<DD>` that follows.
+fn bookmark_entry(indent: &str, alias: &str, shortcut: &Shortcut) -> String {
+    let mut attrs = format!(" HREF=\"{}\"", encode_minimal(&shortcut.url));
+    if shortcut.created_at != 0 {
+        attrs.push_str(&format!(" ADD_DATE=\"{}\"", shortcut.created_at));
+    }
+    if shortcut.updated_at != 0 {
+        attrs.push_str(&format!(" LAST_MODIFIED=\"{}\"", shortcut.updated_at));
+    }
+    attrs.push_str(&format!(" SHORTCUTURL=\"{}\"", encode_minimal(alias)));
+    if !shortcut.tags.is_empty() {
+        attrs.push_str(&format!(" TAGS=\"{}\"", encode_minimal(&shortcut.tags.join(","))));
+    }
+
+    let title = if shortcut.description.is_empty() { alias } else { &shortcut.description };
+    let mut entry = format!("{}<DT><A{}>{}</A>\n", indent, attrs, encode_minimal(title));
+    if !shortcut.notes.is_empty() {
+        entry.push_str(&format!("{}<DD>{}\n", indent, encode_minimal(&shortcut.notes)));
+    }
+    entry
+}
+
+// Reads the `<A>` entries out of a Netscape bookmark file (the format every browser exports).
+// Folders are ignored; the alias comes from the bookmark keyword when there is one, otherwise
+// from the title. Later bookmarks with an alias already seen are dropped.
+pub fn parse_bookmarks(html: &str) -> Vec<ImportedBookmark> {
+    let mut found: Vec<ImportedBookmark> = Vec::new();
+    let mut open_anchor: Option<(Vec<(String, String)>, String)> = None;
+    let mut in_dd = false;
+    // Whether the last closed <A> was kept; a dropped duplicate's <DD> is dropped too
+    let mut kept = false;
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let start = html[pos..].find('<').map(|i| pos + i).unwrap_or(html.len());
+        let text = &html[pos..start];
+
+        // Text belongs to the open <A> (its title) or to the <DD> after the last bookmark
+        if let Some((_, title)) = open_anchor.as_mut() {
+            title.push_str(text);
+        } else if in_dd && kept && let Some(last) = found.last_mut() {
+            last.shortcut.notes.push_str(text);
+        }
+        if start >= html.len() {
+            break;
+        }
+
+        let end = html[start..].find('>').map(|i| start + i).unwrap_or(html.len());
+        let tag = &html[start + 1..end];
+        pos = (end + 1).min(html.len());
+
+        let name = tag.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+        in_dd = name == "dd";
+        match name.as_str() {
+            "a" => open_anchor = Some((parse_attributes(tag), String::new())),
+            "/a" => {
+                kept = false;
+                if let Some((attrs, title)) = open_anchor.take()
+                    && let Some(bookmark) = bookmark_from_anchor(&attrs, &title)
+                    && !found.iter().any(|b| b.alias == bookmark.alias)
+                {
+                    found.push(bookmark);
+                    kept = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for bookmark in &mut found {
+        bookmark.shortcut.notes = decode_text(&bookmark.shortcut.notes);
+    }
+    found
+}
+
+fn bookmark_from_anchor(attrs: &[(String, String)], title: &str) -> Option<ImportedBookmark> {
+    let attr = |name: &str| attrs.iter().find(|(k, _)| k == name).map(|(_, v)| decode_text(v));
+
+    let url = attr("href").unwrap_or_default();
+    // Browser-internal entries (smart folders, bookmarklets) have nothing to redirect to
+    if url.is_empty() || url.starts_with("place:") || url.starts_with("javascript:") {
+        return None;
+    }
+
+    let title = decode_text(title);
+    let alias = attr("shortcuturl")
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .or_else(|| Some(slugify(&title)).filter(|s| !s.is_empty()))
+        .or_else(|| Some(slugify(host_of(&url))).filter(|s| !s.is_empty()))?;
+
+    let timestamp = |name: &str| attr(name).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+    let created_at = timestamp("add_date");
+    let tags = attr("tags")
+        .map(|t| t.split(',').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect())
+        .unwrap_or_default();
+
+    Some(ImportedBookmark {
+        shortcut: Shortcut {
+            url,
+            // A title that is just the alias carries no extra information
+            description: if title == alias { String::new() } else { title },
+            tags,
+            owner: String::new(),
+            notes: String::new(),
+            created_at,
+            updated_at: timestamp("last_modified").max(created_at),
+            locked: false,
+            active_from: 0,
+            expires_at: 0,
+        },
+        alias,
+    })
+}
+
+// Parses `NAME="value"` pairs from the inside of a tag (names are lower-cased)
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    // Skip the tag name itself
+    let mut rest = tag.trim_start().trim_start_matches(|c: char| !c.is_whitespace());
+
+    loop {
+        rest = rest.trim_start();
+        let name_len = rest.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(rest.len());
+        if name_len == 0 {
+            break;
+        }
+        let name = rest[..name_len].to_ascii_lowercase();
+        rest = rest[name_len..].trim_start();
+
+        let value = if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let inner = &after[1..];
+                    let close = inner.find(q).unwrap_or(inner.len());
+                    rest = inner.get(close + 1..).unwrap_or("");
+                    inner[..close].to_string()
+                }
+                _ => {
+                    let len = after.find(char::is_whitespace).unwrap_or(after.len());
+                    rest = &after[len..];
+                    after[..len].to_string()
+                }
+            }
+        } else {
+            String::new()
+        };
+        attrs.push((name, value));
+    }
+    attrs
+}
+
+fn decode_text(text: &str) -> String {
+    let trimmed = text.trim();
+    decode_html(trimmed).unwrap_or_else(|_| trimmed.to_string())
+}
+
+// Turns a bookmark title into an alias: lower case words joined by dashes
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+fn import_status<'a>(collections: &'a [ShortcutCollection], bookmark: &ImportedBookmark, unlocked: bool) -> ImportStatus<'a> {
+    match find_shortcut(collections, &bookmark.alias) {
+        None => ImportStatus::New,
+        Some((collection, _)) if collection.hidden && !unlocked => ImportStatus::Hidden,
+        Some((_, existing)) if existing.url == bookmark.shortcut.url => ImportStatus::Unchanged,
+        Some((collection, _)) if collection.directory => ImportStatus::ReadOnly(collection),
+        Some((collection, existing)) => ImportStatus::Conflict(collection, existing),
+    }
+}
+
+// Handler for GET /shortcuts/import
+#[get("/shortcuts/import")]
+pub async fn shortcuts_import_get(req: HttpRequest, state: Data<Arc<AppState>>) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+    let current_theme = state.current_theme.lock().unwrap();
+    let unlocked = hidden::is_unlocked(&req, &state);
+
+    let collection_options: String = collections
+        .iter()
+        .filter(|c| !c.directory && (unlocked || !c.hidden))
+        .map(|c| {
+            format!(
+                "<option value=\"{0}\">{0}{1}</option>",
+                encode_minimal(&c.name),
+                if c.hidden { " (hidden)" } else { "" }
+            )
+        })
+        .collect();
+
+    let content = format!(
+        r#"
+    <div class="settings-form bookmarks-page">
+        <h1>Export Shortcuts</h1>
+        <p>Download every collection as a bookmarks file that any browser can import. Each collection becomes a folder, with a sub-folder per tag.</p>
+        <div class="theme-action-buttons">
+            <a href="/shortcuts/export?format=bookmarks"><button type="button" class="form-submit-btn">Download Bookmarks</button></a>
+        </div>
+
+        <h1>Import Bookmarks</h1>
+        <p>Pick a bookmarks HTML file exported from a browser (or paste its contents). The browser keyword is used as the alias when there is one, otherwise the title. You can review conflicts before anything is saved.</p>
+        <form action="/shortcuts/import/preview" method="POST">
+            <div class="settings-grid">
+                <div>
+                    <label for="bookmarks_file">Bookmarks file:</label>
+                    <input type="file" id="bookmarks_file" accept=".html,.htm,text/html">
+                </div>
+                <div>
+                    <label for="import_collection">Add new shortcuts to:</label>
+                    <select id="import_collection" name="collection">
+                        {collection_options}
+                    </select>
+                </div>
+            </div>
+            <textarea id="bookmarks" name="bookmarks" rows="10" style="width: 100%;" placeholder="&lt;!DOCTYPE NETSCAPE-Bookmark-file-1&gt; ..." required></textarea>
+            <div class="theme-action-buttons">
+                <button type="submit" class="form-submit-btn">Preview Import</button>
+            </div>
+        </form>
+    </div>
+    <script>
+        document.getElementById('bookmarks_file').addEventListener('change', (e) => {{
+            const file = e.target.files[0];
+            if (!file) return;
+            const reader = new FileReader();
+            reader.onload = (e) => {{
+                document.getElementById('bookmarks').value = e.target.result;
+            }};
+            reader.readAsText(file);
+        }});
+    </script>
+"#,
+        collection_options = collection_options,
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Import / Export Shortcuts", &content, &current_theme))
+}
+
+// Handler for POST /shortcuts/import/preview
+// Shows what an import would do without changing anything: new aliases are selected by
+// default, conflicting ones have to be ticked to be overwritten.
+#[post("/shortcuts/import/preview")]
+pub async fn shortcuts_import_preview(
+    req: HttpRequest,
+    form: Form<ImportPreviewForm>,
+    state: Data<Arc<AppState>>,
+) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+    let current_theme = state.current_theme.lock().unwrap();
+    let unlocked = hidden::is_unlocked(&req, &state);
+
+    if !collections.iter().any(|c| c.name == form.collection && !c.directory && (unlocked || !c.hidden)) {
+        return HttpResponse::BadRequest().body("Unknown shortcut collection.");
+    }
+
+    let bookmarks = parse_bookmarks(&form.bookmarks);
+    if bookmarks.is_empty() {
+        return HttpResponse::BadRequest().body("No bookmarks found in the uploaded file.");
+    }
+
+    let (mut new, mut unchanged, mut conflicts) = (0, 0, 0);
+    let rows = bookmarks
+        .iter()
+        .map(|b| {
+            let (checkbox, status) = match import_status(&collections, b, unlocked) {
+                ImportStatus::New => {
+                    new += 1;
+                    ("checked", "New".to_string())
+                }
+                ImportStatus::Unchanged => {
+                    unchanged += 1;
+                    ("disabled", "Already exists".to_string())
+                }
+                ImportStatus::Hidden => {
+                    conflicts += 1;
+                    ("disabled", "<strong>Conflict</strong>: taken by a hidden shortcut. Unlock <a href=\"/hidden\">/hidden</a> to overwrite it.".to_string())
+                }
+                ImportStatus::ReadOnly(collection) => {
+                    conflicts += 1;
+                    let source = collection.sources.get(&b.alias).unwrap_or(&collection.file);
+                    ("disabled", format!("<strong>Conflict</strong>: comes from {}, which is read-only.", encode_minimal(source)))
+                }
+                ImportStatus::Conflict(collection, existing) => {
+                    conflicts += 1;
+                    (
+                        "",
+                        format!(
+                            "<strong>Conflict</strong>: currently <span class=\"url\">{}</span> in {}. Tick to overwrite.",
+                            encode_minimal(&existing.url),
+                            encode_minimal(&collection.name)
+                        ),
+                    )
+                }
+            };
+            format!(
+                "<tr><td><input type=\"checkbox\" name=\"alias\" value=\"{alias}\" {checkbox}></td><td>{alias}</td><td class=\"url\">{url}</td><td>{description}</td><td>{status}</td></tr>",
+                alias = encode_minimal(&b.alias),
+                checkbox = checkbox,
+                url = encode_minimal(&b.shortcut.url),
+                description = encode_minimal(&b.shortcut.description),
+                status = status,
+            )
+        })
+        .collect::<String>();
+
+    let content = format!(
+        r#"
+    <div class="settings-form bookmarks-page">
+        <h1>Import Preview</h1>
+        <p>{total} bookmarks: {new} new, {conflicts} conflicting, {unchanged} already present. New shortcuts go to <strong>{collection}</strong>; overwritten ones stay in the collection they are in.</p>
+        <form action="/shortcuts/import" method="POST">
+            <input type="hidden" name="collection" value="{collection}">
+            <textarea name="bookmarks" hidden>{bookmarks}</textarea>
+            <table class="grid">
+                <thead><tr><th></th><th>Shortcut</th><th>Destination URL</th><th>Description</th><th>Status</th></tr></thead>
+                <tbody>{rows}</tbody>
+            </table>
+            <div class="theme-action-buttons">
+                <button type="submit" class="form-submit-btn">Import Selected</button>
+                <a href="/shortcuts/import"><button type="button" class="form-submit-btn">Cancel</button></a>
+            </div>
+        </form>
+    </div>
+"#,
+        total = bookmarks.len(),
+        new = new,
+        conflicts = conflicts,
+        unchanged = unchanged,
+        collection = encode_minimal(&form.collection),
+        bookmarks = encode_minimal(&form.bookmarks),
+        rows = rows,
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Import Preview", &content, &current_theme))
+}
+
+// Handler for POST /shortcuts/import
+// The form repeats `alias` once per ticked row, so it is read as plain pairs.
+#[post("/shortcuts/import")]
+pub async fn shortcuts_import(req: HttpRequest, form: Form<Vec<(String, String)>>, state: Data<Arc<AppState>>) -> impl Responder {
+    let field = |name: &str| form.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str()).unwrap_or("");
+    let selected: HashSet<&str> = form.iter().filter(|(k, _)| k == "alias").map(|(_, v)| v.as_str()).collect();
+
+    let mut collections = state.collections.lock().unwrap();
+    let unlocked = hidden::is_unlocked(&req, &state);
+    let Some(target) = collections
+        .iter()
+        .position(|c| c.name == field("collection") && !c.directory && (unlocked || !c.hidden))
+    else {
+        return HttpResponse::BadRequest().body("Unknown shortcut collection.");
+    };
+
+    let bookmarks: Vec<ImportedBookmark> = parse_bookmarks(field("bookmarks"))
+        .into_iter()
+        .filter(|b| selected.contains(b.alias.as_str()))
+        .collect();
+
+    // Keep a copy so a failed chain check or save leaves every collection as it was
+    let snapshot: Vec<_> = collections.iter().map(|c| c.shortcuts.clone()).collect();
+    let now = current_ts();
+    let mut touched = HashSet::new();
+    let mut changes = Vec::new();
+    let who = actor(&req);
+
+    for bookmark in &bookmarks {
+        // An existing alias is overwritten where it lives, so the import can't shadow it
+        let index = collections
+            .iter()
+            .position(|c| c.shortcuts.contains_key(&bookmark.alias))
+            .unwrap_or(target);
+        if collections[index].directory || (collections[index].hidden && !unlocked) {
+            continue;
+        }
+
+        // Bookmarks only carry the URL and details; the rest of an overwritten shortcut stays
+        let mut shortcut = bookmark.shortcut.clone();
+        if let Some(existing) = collections[index].shortcuts.get(&bookmark.alias) {
+            shortcut.owner = existing.owner.clone();
+            shortcut.locked = existing.locked;
+            shortcut.active_from = existing.active_from;
+            shortcut.expires_at = existing.expires_at;
+            if existing.created_at != 0 {
+                shortcut.created_at = existing.created_at;
+            }
+        }
+        if shortcut.created_at == 0 {
+            shortcut.created_at = now;
+        }
+        shortcut.updated_at = now;
+
+        let previous = collections[index].shortcuts.insert(bookmark.alias.clone(), shortcut.clone());
+        let mut change = Change::new(&who, &collections[index].name, &bookmark.alias, previous.as_ref(), Some(&shortcut));
+        change.note = "bookmarks import".to_string();
+        changes.push(change);
+        touched.insert(index);
+    }
+
+    for bookmark in &bookmarks {
+        if let Err(e) = check_chain(&state, &collections, &bookmark.alias) {
+            for (collection, shortcuts) in collections.iter_mut().zip(snapshot) {
+                collection.shortcuts = shortcuts;
+            }
+            return HttpResponse::BadRequest().body(format!("Import of '{}' rejected: {}", bookmark.alias, e));
+        }
+    }
+
+    let touched: Vec<usize> = touched.into_iter().collect();
+    for (position, &index) in touched.iter().enumerate() {
+        if let Err(e) = save_collection(&mut collections[index]) {
+            // Undo the whole import, including the files that were already written
+            for (collection, shortcuts) in collections.iter_mut().zip(snapshot) {
+                collection.shortcuts = shortcuts;
+            }
+            for &saved in &touched[..position] {
+                if save_collection(&mut collections[saved]).is_err() {
+                    eprintln!("Failed to restore {}", collections[saved].file);
+                }
+            }
+            return e.into_response();
+        }
+    }
+    for change in &changes {
+        history::record(change);
+    }
+
+    HttpResponse::Found()
+        .append_header(("Location", "/"))
+        .finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::CollectionConfig;
+    use std::collections::HashMap;
+
+    // Trimmed from a Firefox export: a keyword bookmark, tags, a folder, a note and a smart folder
+    const FIREFOX_EXPORT: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<!-- This is an automatically generated file.
+     It will be read and overwritten.
+     DO NOT EDIT! -->
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<meta http-equiv="Content-Security-Policy"
+      content="default-src 'self'; script-src 'none'; img-src data: *; object-src 'none'"></meta>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+
+<DL><p>
+    <DT><A HREF="place:parent=toolbar_____&sort=12" ADD_DATE="1700000000">Recent Tags</A>
+    <DT><H3 ADD_DATE="1700000000" LAST_MODIFIED="1700000500">Work</H3>
+    <DL><p>
+        <DT><A HREF="https://jira.example.com/browse/" ADD_DATE="1700000100" LAST_MODIFIED="1700000200" SHORTCUTURL="jira" TAGS="work,tickets">Jira &amp; Confluence</A>
+        <DD>Team board, see &quot;PROJ&quot;
+        <DT><A HREF="https://news.ycombinator.com/" ADD_DATE="1700000300" ICON="data:image/png;base64,iVBORw0KGgo=">Hacker News</A>
+    </DL><p>
+    <DT><A HREF='https://example.com/?a=1&amp;b=2' ADD_DATE=1700000400>example.com</A>
+    <DT><A HREF="javascript:alert(1)">Bookmarklet</A>
+    <DT><A HREF="https://other.example/" SHORTCUTURL="jira">Duplicate keyword</A>
+</DL>
+"#;
+
+    #[test]
+    fn parses_a_browser_export() {
+        let bookmarks = parse_bookmarks(FIREFOX_EXPORT);
+        let aliases: Vec<&str> = bookmarks.iter().map(|b| b.alias.as_str()).collect();
+        // Smart folders and bookmarklets are skipped; the first `jira` wins
+        assert_eq!(aliases, ["jira", "hacker-news", "example-com"]);
+
+        let jira = &bookmarks[0].shortcut;
+        assert_eq!(jira.url, "https://jira.example.com/browse/");
+        assert_eq!(jira.description, "Jira & Confluence");
+        assert_eq!(jira.tags, ["work", "tickets"]);
+        assert_eq!(jira.notes, "Team board, see \"PROJ\"");
+        assert_eq!((jira.created_at, jira.updated_at), (1700000100, 1700000200));
+
+        let news = &bookmarks[1].shortcut;
+        assert_eq!(news.description, "Hacker News");
+        assert!(news.notes.is_empty() && news.tags.is_empty());
+        assert_eq!(news.updated_at, news.created_at);
+
+        // Single-quoted and unquoted attribute values, entities decoded
+        let example = &bookmarks[2].shortcut;
+        assert_eq!(example.url, "https://example.com/?a=1&b=2");
+        assert_eq!(example.created_at, 1700000400);
+    }
+
+    #[test]
+    fn parses_attributes() {
+        let attrs = parse_attributes(r#"A HREF="https://x.example/?q=a b" shortcutURL='kw' ADD_DATE=17 PERSONAL_TOOLBAR_FOLDER"#);
+        let expected = [
+            ("href", "https://x.example/?q=a b"),
+            ("shortcuturl", "kw"),
+            ("add_date", "17"),
+            ("personal_toolbar_folder", ""),
+        ];
+        let expected: Vec<(String, String)> = expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(attrs, expected);
+
+        assert!(parse_attributes("DT").is_empty());
+    }
+
+    #[test]
+    fn alias_falls_back_to_title_then_host() {
+        let bookmarks = parse_bookmarks(r#"<DT><A HREF="https://docs.rs/">Docs.rs: Rust docs!</A><DT><A HREF="https://www.example.org/x"></A>"#);
+        let aliases: Vec<&str> = bookmarks.iter().map(|b| b.alias.as_str()).collect();
+        assert_eq!(aliases, ["docs-rs-rust-docs", "example-org"]);
+    }
+
+    #[test]
+    fn exported_file_reads_back() {
+        let mut shortcuts = HashMap::new();
+        shortcuts.insert(
+            "gh".to_string(),
+            Shortcut {
+                url: "https://github.com/?tab=a&b".to_string(),
+                description: "GitHub <home>".to_string(),
+                tags: vec!["code".to_string(), "oss".to_string()],
+                notes: "Use the org view".to_string(),
+                created_at: 1700000000,
+                updated_at: 1700000100,
+                ..Default::default()
+            },
+        );
+        shortcuts.insert("wiki".to_string(), Shortcut { url: "https://wiki.example/".to_string(), ..Default::default() });
+        let config = CollectionConfig {
+            name: "Shortcuts".to_string(),
+            file: "shortcuts.json".to_string(),
+            hidden: false,
+            priority: None,
+            directory: false,
+        };
+        let collection = ShortcutCollection::new(&config, 0, shortcuts.clone());
+
+        let bookmarks = parse_bookmarks(&render_bookmarks_file([&collection]));
+        // A shortcut with two tags is in two folders, but is read back once
+        assert_eq!(bookmarks.len(), 2);
+        for bookmark in bookmarks {
+            assert!(shortcuts[&bookmark.alias] == bookmark.shortcut, "{} changed", bookmark.alias);
+        }
+    }
+}
@@ -15,24 +15,23 @@ pub async fn calculator_get(state: Data<Arc<AppState>>) -> impl Responder {
 }
 
 fn render_calculator_page(current_theme: &Theme) -> String {
-    let style = format!(
-        r#"
+    let style = r#"
 <style>
     /* Calculator Custom Styles */
-    .calculator-app {{
+    .calculator-app {
         display: flex;
         gap: 20px;
         max-width: 1000px; /* Increased max width for scientific mode */
         margin: 0 auto;
         padding: 20px;
         flex-direction: column;
-    }}
-    @media (min-width: 900px) {{ /* Increased breakpoint for scientific mode */
-        .calculator-app {{
+    }
+    @media (min-width: 900px) { /* Increased breakpoint for scientific mode */
+        .calculator-app {
             flex-direction: row;
-        }}
-    }}
-    .calculator-container {{
+        }
+    }
+    .calculator-container {
         flex: 3;
         background-color: var(--secondary-bg);
         border: 1px solid var(--border-color);
@@ -41,12 +40,12 @@ fn render_calculator_page(current_theme: &Theme) -> String {
         padding: 15px;
         display: flex;
         flex-direction: column;
-    }}
-    .mode-toggle {{
+    }
+    .mode-toggle {
         margin-bottom: 10px;
         align-self: flex-start;
-    }}
-    .display {{
+    }
+    .display {
         background-color: var(--primary-bg);
         color: var(--text-color);
         padding: 15px;
@@ -58,35 +57,35 @@ fn render_calculator_page(current_theme: &Theme) -> String {
         overflow-x: auto;
         white-space: nowrap;
         line-height: 1.2;
-    }}
-    .current-input {{
+    }
+    .current-input {
         font-size: 1.5em;
         color: var(--text-color);
         min-height: 30px;
-    }}
-    .buttons-wrapper {{
+    }
+    .buttons-wrapper {
         display: flex;
         gap: 10px;
         flex-grow: 1; /* Allow wrapper to grow */
-    }}
+    }
     
     /* Scientific Buttons */
-    .scientific-buttons {{
+    .scientific-buttons {
         display: none; /* Hidden by default */
         grid-template-columns: repeat(3, 1fr); 
         gap: 10px;
         flex: 0 0 30%; /* Fixed width for scientific functions */
-    }}
+    }
     
     /* Standard Buttons */
-    .standard-buttons {{
+    .standard-buttons {
         display: grid;
         grid-template-columns: repeat(4, 1fr);
         gap: 10px;
         flex: 1 1 65%; /* Take up the remaining space */
-    }}
+    }
     
-    .calc-button {{
+    .calc-button {
         background-color: var(--tertiary-bg);
         color: var(--text-color);
         border: none;
@@ -96,31 +95,31 @@ fn render_calculator_page(current_theme: &Theme) -> String {
         cursor: pointer;
         transition: background-color 0.1s, box-shadow 0.1s;
         box-shadow: 0 2px var(--border-color);
-    }}
-    .calc-button:active {{
+    }
+    .calc-button:active {
         box-shadow: none;
         transform: translateY(2px);
-    }}
-    .calc-button.operator {{
+    }
+    .calc-button.operator {
         background-color: var(--link-color);
         color: var(--primary-bg);
-    }}
-    .calc-button.scientific {{
+    }
+    .calc-button.scientific {
         background-color: #555; /* Darker background for scientific functions */
         color: var(--text-color);
-    }}
-    .calc-button.clear {{
+    }
+    .calc-button.clear {
         background-color: #d33;
         color: white;
-    }}
-    .calc-button.equals {{
+    }
+    .calc-button.equals {
         background-color: #4CAF50;
         color: white;
         grid-column: span 2;
-    }}
+    }
     
     /* History Styles */
-    .history-container {{
+    .history-container {
         flex: 1;
         background-color: var(--secondary-bg);
         border: 1px solid var(--border-color);
@@ -128,41 +127,40 @@ fn render_calculator_page(current_theme: &Theme) -> String {
         padding: 15px;
         max-height: 500px; /* Limit height for history */
         overflow-y: auto;
-    }}
-    @media (max-width: 899px) {{
-        .history-container {{
+    }
+    @media (max-width: 899px) {
+        .history-container {
             max-height: 250px; 
-        }}
-    }}
-    .history-container h2 {{
+        }
+    }
+    .history-container h2 {
         margin-top: 0;
         border-bottom: 1px solid var(--border-color);
         padding-bottom: 5px;
-    }}
-    #history-list {{
+    }
+    #history-list {
         list-style: none;
         padding: 0;
-    }}
-    #history-list li {{
+    }
+    #history-list li {
         border-bottom: 1px dashed var(--border-color);
         padding: 8px 0;
         font-size: 0.9em;
         cursor: pointer;
-    }}
-    #history-list li:hover {{
+    }
+    #history-list li:hover {
         background-color: var(--tertiary-bg);
         border-radius: 4px;
         padding-left: 5px;
-    }}
-    .history-expression {{
+    }
+    .history-expression {
         color: #aaa;
-    }}
-    .history-result {{
+    }
+    .history-result {
         font-weight: bold;
-    }}
+    }
 </style>
-        "#,
-    );
+        "#.to_string();
 
     let html_content = r#"
     <div class="calculator-app">
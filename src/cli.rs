@@ -0,0 +1,725 @@
+// Command-line subcommands: `go_service add gh https://github.com`, `list`, `rm`, `resolve`,
+// `import` and `export`. Without a command (or with `serve`) the binary starts the server.
+//
+// When a go_service instance answers on --server (default: this machine, on the configured
+// port) the commands go through its REST API, so the running server sees the change straight
+// away and nothing it holds in memory gets overwritten. Otherwise they work on the JSON files
+// in the data directory, through the same code paths the web forms use.
+
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    time::Duration,
+};
+
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::bookmarks::{parse_bookmarks, render_bookmarks_file};
+use crate::clock::parse_datetime;
+use crate::config;
+use crate::elements::shortcut::{
+    create_shortcut, find_shortcut, parse_shortcuts, remove_shortcut, shortcuts_json, update_shortcut,
+    ShortcutInput,
+};
+use crate::history::cli_actor;
+use crate::http_client;
+use crate::resolve::{encode_capture, encode_component, Resolver};
+use crate::variables::VariableContext;
+
+const USAGE: &str = "Usage: go_service [command] [options]
+
+Commands:
+  serve                        Start the web server (the default)
+  add <alias> <url>            Add a shortcut
+      [--collection NAME] [--description TEXT] [--tags a,b] [--owner NAME] [--notes TEXT]
+      [--active-from DATE] [--expires DATE]
+                               Dates are UTC, e.g. 2024-05-01 or \"2024-05-01 09:00\"
+  list [--collection NAME] [--tag TAG]
+                               List shortcuts (alias, URL, collection), tab separated
+  rm <alias>                   Delete a shortcut
+  resolve <path>               Print where /<path> redirects to
+  import <file> [--collection NAME] [--overwrite]
+                               Import a shortcuts JSON file or a browser bookmarks export;
+                               existing aliases are skipped unless --overwrite is given
+  export [--format json|bookmarks] [--collection NAME]
+                               Write shortcuts to stdout
+
+Options:
+  --server URL                 Use the instance at URL (also GO_SERVICE_URL)
+  --token TOKEN                API token for a server with authentication on (also
+                               GO_SERVICE_TOKEN; defaults to the configured auth_token)
+  --local                      Work on the JSON files in the data directory even if a
+                               server is running";
+
+// Options that don't take a value
+const SWITCHES: &[&str] = &["local", "overwrite", "help"];
+// How long a request to the server may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    switches: HashSet<String>,
+}
+
+impl Args {
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    // The nth positional argument, or a usage error naming it
+    fn required(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional.get(index).map(String::as_str).ok_or_else(|| format!("Missing <{}>.", name))
+    }
+}
+
+// Splits `--name value`, `--name=value` and switches from the positional arguments
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args { positional: Vec::new(), options: HashMap::new(), switches: HashSet::new() };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(name) = arg.strip_prefix("--") else {
+            parsed.positional.push(arg.clone());
+            continue;
+        };
+        if let Some((name, value)) = name.split_once('=') {
+            parsed.options.insert(name.to_string(), value.to_string());
+        } else if SWITCHES.contains(&name) {
+            parsed.switches.insert(name.to_string());
+        } else {
+            let value = iter.next().ok_or_else(|| format!("--{} needs a value.", name))?;
+            parsed.options.insert(name.to_string(), value.clone());
+        }
+    }
+    Ok(parsed)
+}
+
+// Runs one command and returns the process exit code: 0 on success, 1 when the command
+// failed, 2 for usage errors.
+pub fn run(args: &[String]) -> i32 {
+    let mut args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => return usage_error(&e),
+    };
+    if args.switch("help") || args.positional.first().is_none_or(|c| c == "help") {
+        println!("{}\n\n{}", USAGE, config::usage());
+        return 0;
+    }
+    // Options may come before or after the command
+    let command = args.positional.remove(0);
+
+    let handler = match command.as_str() {
+        "add" => cmd_add,
+        "list" => cmd_list,
+        "rm" => cmd_rm,
+        "resolve" => cmd_resolve,
+        "import" => cmd_import,
+        "export" => cmd_export,
+        "serve" => return usage_error("serve takes no arguments other than configuration flags."),
+        other => return usage_error(&format!("Unknown command '{}'.", other)),
+    };
+    let result = connect(&args).and_then(|backend| handler(&backend, &args));
+
+    match result {
+        Ok(()) => 0,
+        Err(CliError::Usage(e)) => usage_error(&e),
+        Err(CliError::Failed(e)) => {
+            eprintln!("go_service: {}", e);
+            1
+        }
+    }
+}
+
+fn usage_error(message: &str) -> i32 {
+    eprintln!("go_service: {}\n\n{}", message, USAGE);
+    2
+}
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failed(message)
+    }
+}
+
+// Where the commands read and write shortcuts
+enum Backend {
+    // A running instance
+    Server(Server),
+    // The JSON files in the current directory
+    Files(Box<AppState>),
+}
+
+// Uses the server when one answers, the files otherwise. An explicit --server that doesn't
+// answer is an error rather than a silent switch to the local files.
+fn connect(args: &Args) -> Result<Backend, CliError> {
+    if args.switch("local") {
+        return files_backend();
+    }
+
+    let explicit = args.option("server").map(String::from).or_else(|| env::var("GO_SERVICE_URL").ok());
+    let url = explicit.clone().unwrap_or_else(|| config::config().local_url());
+    let token = args
+        .option("token")
+        .map(String::from)
+        .or_else(|| env::var("GO_SERVICE_TOKEN").ok())
+        .unwrap_or_else(|| config::config().auth_token.clone());
+    let server = Server { url: url.trim_end_matches('/').to_string(), token };
+
+    match server.request("GET", "/api/shortcuts?collection=", None) {
+        Ok((200, _)) => Ok(Backend::Server(server)),
+        // Editing the files behind a running server's back would be undone by its next save
+        Ok((401, _)) => Err(format!(
+            "{} requires authentication; pass --token or set GO_SERVICE_TOKEN.",
+            server.url
+        )
+        .into()),
+        _ if explicit.is_none() => files_backend(),
+        Ok((status, _)) => Err(format!("{} answered with HTTP {}; is it a go_service instance?", server.url, status).into()),
+        Err(e) => Err(format!("Could not reach {}: {}", server.url, e).into()),
+    }
+}
+
+fn files_backend() -> Result<Backend, CliError> {
+    config::ensure_data_dir()
+        .map_err(|e| format!("Failed to create {}: {}", config::config().data_dir, e))?;
+    Ok(Backend::Files(Box::new(crate::load_app_state())))
+}
+
+// A running instance: base URL and the API token to send (empty for none)
+struct Server {
+    url: String,
+    token: String,
+}
+
+impl Server {
+    // One HTTP request, `path` relative to the base URL. Returns the status and body.
+    fn request(&self, method: &str, path: &str, body: Option<&Value>) -> Result<(u16, String), String> {
+        let authorization = format!("Bearer {}", self.token);
+        let mut headers = Vec::new();
+        if !self.token.is_empty() {
+            headers.push(("Authorization", authorization.as_str()));
+        }
+        let body = body.map(Value::to_string);
+        if body.is_some() {
+            headers.push(("Content-Type", "application/json"));
+        }
+
+        let url = format!("{}{}", self.url, path);
+        let response = http_client::send(method, &url, &headers, body.as_deref().map(str::as_bytes), REQUEST_TIMEOUT)?;
+        Ok((response.status, response.text()))
+    }
+}
+
+// The message from an API error body, or the raw body when it isn't one
+fn api_message(status: u16, body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v["error"]["message"].as_str().map(String::from))
+        .unwrap_or_else(|| format!("HTTP {}: {}", status, body.trim()))
+}
+
+fn shortcut_path(key: &str) -> String {
+    format!("/api/shortcuts/{}", encode_capture(key))
+}
+
+fn shortcut_body(key: &str, shortcut: &Shortcut, collection: Option<&str>) -> Value {
+    let mut body = json!({
+        "key": key,
+        "url": shortcut.url,
+        "description": shortcut.description,
+        "tags": shortcut.tags,
+        "owner": shortcut.owner,
+        "notes": shortcut.notes,
+        "active_from": shortcut.active_from,
+        "expires_at": shortcut.expires_at,
+    });
+    if let Some(collection) = collection {
+        body["collection"] = json!(collection);
+    }
+    body
+}
+
+fn shortcut_input(shortcut: &Shortcut) -> ShortcutInput {
+    let mut input = ShortcutInput::new(&shortcut.url, &shortcut.description, &shortcut.tags, &shortcut.owner, &shortcut.notes);
+    input.active_from = Some(shortcut.active_from);
+    input.expires_at = Some(shortcut.expires_at);
+    input
+}
+
+// A shortcut and the collection it lives in, as listed by either backend
+struct Listed {
+    key: String,
+    collection: String,
+    shortcut: Shortcut,
+}
+
+impl Backend {
+    // Every shortcut in resolution order, optionally narrowed to a collection or tag
+    fn list(&self, collection: Option<&str>, tag: Option<&str>) -> Result<Vec<Listed>, String> {
+        match self {
+            Backend::Server(server) => {
+                let mut url = "/api/shortcuts".to_string();
+                let mut sep = '?';
+                for (name, value) in [("collection", collection), ("tag", tag)] {
+                    if let Some(value) = value {
+                        url.push_str(&format!("{}{}={}", sep, name, encode_component(value)));
+                        sep = '&';
+                    }
+                }
+                let (status, body) = server.request("GET", &url, None)?;
+                if status != 200 {
+                    return Err(api_message(status, &body));
+                }
+                let entries: Vec<Value> = serde_json::from_str(&body).map_err(|e| format!("Unexpected response: {}", e))?;
+                entries
+                    .into_iter()
+                    .map(|entry| {
+                        Ok(Listed {
+                            key: entry["key"].as_str().unwrap_or_default().to_string(),
+                            collection: entry["collection"].as_str().unwrap_or_default().to_string(),
+                            shortcut: serde_json::from_value(entry).map_err(|e| format!("Unexpected response: {}", e))?,
+                        })
+                    })
+                    .collect()
+            }
+            Backend::Files(state) => {
+                let collections = state.collections.lock().unwrap();
+                let mut list = Vec::new();
+                for c in collections.iter().filter(|c| collection.is_none_or(|name| name == c.name)) {
+                    let mut keys: Vec<&String> = c.shortcuts.keys().collect();
+                    keys.sort();
+                    for key in keys {
+                        let shortcut = &c.shortcuts[key];
+                        if tag.is_some_and(|tag| !shortcut.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))) {
+                            continue;
+                        }
+                        list.push(Listed { key: key.clone(), collection: c.name.clone(), shortcut: shortcut.clone() });
+                    }
+                }
+                Ok(list)
+            }
+        }
+    }
+
+    // Adds a new alias; `collection` defaults to the first visible, writable one
+    fn add(&self, key: &str, shortcut: &Shortcut, collection: Option<&str>) -> Result<(), String> {
+        match self {
+            Backend::Server(server) => {
+                let body = shortcut_body(key, shortcut, collection);
+                match server.request("POST", "/api/shortcuts", Some(&body))? {
+                    (201, _) => Ok(()),
+                    (status, body) => Err(api_message(status, &body)),
+                }
+            }
+            Backend::Files(state) => {
+                let mut collections = state.collections.lock().unwrap();
+                let index = target_collection(&collections, collection)?;
+                if let Some((existing, _)) = find_shortcut(&collections, key) {
+                    return Err(format!("Shortcut '{}' already exists in {}.", key, existing.name));
+                }
+                create_shortcut(state, &mut collections, index, key, shortcut_input(shortcut), &cli_actor()).map_err(|e| e.message())
+            }
+        }
+    }
+
+    // Replaces the URL and details of an existing alias, where it lives
+    fn replace(&self, key: &str, shortcut: &Shortcut) -> Result<(), String> {
+        match self {
+            Backend::Server(server) => {
+                let body = shortcut_body(key, shortcut, None);
+                match server.request("PUT", &shortcut_path(key), Some(&body))? {
+                    (200, _) => Ok(()),
+                    (status, body) => Err(api_message(status, &body)),
+                }
+            }
+            Backend::Files(state) => {
+                let mut collections = state.collections.lock().unwrap();
+                update_shortcut(state, &mut collections, key, key, shortcut_input(shortcut), &cli_actor()).map_err(|e| e.message())
+            }
+        }
+    }
+
+    fn remove(&self, key: &str) -> Result<(), String> {
+        match self {
+            Backend::Server(server) => match server.request("DELETE", &shortcut_path(key), None)? {
+                (204, _) => Ok(()),
+                (status, body) => Err(api_message(status, &body)),
+            },
+            Backend::Files(state) => {
+                remove_shortcut(&mut state.collections.lock().unwrap(), key, &cli_actor()).map_err(|e| e.message())
+            }
+        }
+    }
+
+    // The URL a request for `path` redirects to. Doesn't count as a hit.
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        match self {
+            Backend::Server(server) => {
+                let url = format!("/api/resolve?path={}", encode_component(path));
+                match server.request("GET", &url, None)? {
+                    (200, body) => serde_json::from_str::<Value>(&body)
+                        .ok()
+                        .and_then(|v| v["url"].as_str().map(String::from))
+                        .ok_or_else(|| format!("Unexpected response: {}", body)),
+                    (status, body) => Err(api_message(status, &body)),
+                }
+            }
+            Backend::Files(state) => {
+                let collections = state.collections.lock().unwrap();
+                let rules = state.rules.lock().unwrap();
+                let go_settings = state.go_settings.lock().unwrap();
+                let resolver = Resolver {
+                    collections: &collections,
+                    rules: &rules,
+                    settings: &go_settings,
+                    variables: VariableContext::default(),
+                };
+                resolver.resolve_request(path).map(|r| r.url).map_err(|e| e.describe())
+            }
+        }
+    }
+
+    fn export_bookmarks(&self) -> Result<String, String> {
+        match self {
+            Backend::Server(server) => match server.request("GET", "/shortcuts/export?format=bookmarks", None)? {
+                (200, body) => Ok(body),
+                (status, body) => Err(api_message(status, &body)),
+            },
+            Backend::Files(state) => Ok(render_bookmarks_file(state.collections.lock().unwrap().iter())),
+        }
+    }
+}
+
+fn target_collection(collections: &[ShortcutCollection], name: Option<&str>) -> Result<usize, String> {
+    match name {
+        Some(name) => collections
+            .iter()
+            .position(|c| c.name == name)
+            .ok_or_else(|| format!("Unknown shortcut collection '{}'.", name)),
+        None => collections
+            .iter()
+            .position(|c| !c.hidden && !c.directory)
+            .ok_or_else(|| "There is no writable shortcut collection.".to_string()),
+    }
+}
+
+fn cmd_add(backend: &Backend, args: &Args) -> Result<(), CliError> {
+    let key = args.required(0, "alias").map_err(CliError::Usage)?.trim();
+    let url = args.required(1, "url").map_err(CliError::Usage)?;
+    let shortcut = Shortcut {
+        url: url.to_string(),
+        description: args.option("description").unwrap_or_default().to_string(),
+        tags: args.option("tags").unwrap_or_default().split(',').map(String::from).collect(),
+        owner: args.option("owner").unwrap_or_default().to_string(),
+        notes: args.option("notes").unwrap_or_default().to_string(),
+        active_from: time_option(args, "active-from")?,
+        expires_at: time_option(args, "expires")?,
+        ..Default::default()
+    };
+    backend.add(key, &shortcut, args.option("collection"))?;
+    println!("Added {} -> {}", key, url.trim());
+    Ok(())
+}
+
+// A `YYYY-MM-DD [HH:MM]` option in UTC as Unix seconds; 0 when not given
+fn time_option(args: &Args, name: &str) -> Result<u64, CliError> {
+    match args.option(name) {
+        None => Ok(0),
+        Some(value) => parse_datetime(value)
+            .ok_or_else(|| CliError::Usage(format!("--{} takes a date like 2024-05-01 or \"2024-05-01 09:00\" (UTC).", name))),
+    }
+}
+
+fn cmd_list(backend: &Backend, args: &Args) -> Result<(), CliError> {
+    for entry in backend.list(args.option("collection"), args.option("tag"))? {
+        println!("{}\t{}\t{}", entry.key, entry.shortcut.url, entry.collection);
+    }
+    Ok(())
+}
+
+fn cmd_rm(backend: &Backend, args: &Args) -> Result<(), CliError> {
+    let key = args.required(0, "alias").map_err(CliError::Usage)?;
+    backend.remove(key)?;
+    println!("Deleted {}", key);
+    Ok(())
+}
+
+fn cmd_resolve(backend: &Backend, args: &Args) -> Result<(), CliError> {
+    let path = args.required(0, "path").map_err(CliError::Usage)?;
+    println!("{}", backend.resolve(path)?);
+    Ok(())
+}
+
+// Reads either format the service writes: a shortcuts JSON file or a bookmarks export
+fn read_import_file(path: &str) -> Result<Vec<(String, Shortcut)>, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    if data.trim_start().starts_with('{') {
+        let mut entries: Vec<(String, Shortcut)> = parse_shortcuts(&data)
+            .map_err(|e| format!("Invalid shortcuts file {}: {}", path, e))?
+            .into_iter()
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    } else {
+        Ok(parse_bookmarks(&data).into_iter().map(|b| (b.alias, b.shortcut)).collect())
+    }
+}
+
+// What an overwrite stores: the imported shortcut, but an owner or schedule the file doesn't
+// have (bookmark exports never do) is kept from the existing alias, as the web import does
+fn keep_existing(imported: &Shortcut, current: &Shortcut) -> Shortcut {
+    let mut shortcut = imported.clone();
+    if shortcut.owner.is_empty() {
+        shortcut.owner = current.owner.clone();
+    }
+    if shortcut.active_from == 0 && shortcut.expires_at == 0 {
+        shortcut.active_from = current.active_from;
+        shortcut.expires_at = current.expires_at;
+    }
+    shortcut
+}
+
+fn cmd_import(backend: &Backend, args: &Args) -> Result<(), CliError> {
+    let path = args.required(0, "file").map_err(CliError::Usage)?;
+    let entries = read_import_file(path)?;
+    if entries.is_empty() {
+        return Err(format!("No shortcuts found in {}.", path).into());
+    }
+
+    let counts = import_entries(backend, &entries, args.option("collection"), args.switch("overwrite"))?;
+    println!("{} added, {} replaced, {} skipped, {} failed", counts.added, counts.replaced, counts.skipped, counts.failed);
+    if counts.failed > 0 {
+        return Err(format!("{} of {} shortcuts could not be imported.", counts.failed, entries.len()).into());
+    }
+    Ok(())
+}
+
+#[derive(Default, PartialEq, Debug)]
+struct ImportCounts {
+    added: usize,
+    replaced: usize,
+    skipped: usize,
+    failed: usize,
+}
+
+// Adds the new aliases and skips or (with `overwrite`) replaces the existing ones, printing a
+// line for each
+fn import_entries(
+    backend: &Backend,
+    entries: &[(String, Shortcut)],
+    collection: Option<&str>,
+    overwrite: bool,
+) -> Result<ImportCounts, String> {
+    let existing: HashMap<String, Shortcut> =
+        backend.list(None, None)?.into_iter().rev().map(|l| (l.key, l.shortcut)).collect();
+
+    let mut counts = ImportCounts::default();
+    for (key, shortcut) in entries {
+        let result = match existing.get(key) {
+            None => backend.add(key, shortcut, collection).map(|()| &mut counts.added),
+            Some(current) if overwrite => backend.replace(key, &keep_existing(shortcut, current)).map(|()| &mut counts.replaced),
+            Some(current) => {
+                if current.url != shortcut.url {
+                    println!("skipped  {} (already exists; use --overwrite to replace it)", key);
+                }
+                counts.skipped += 1;
+                continue;
+            }
+        };
+        match result {
+            Ok(count) => {
+                *count += 1;
+                println!("imported {} -> {}", key, shortcut.url);
+            }
+            Err(e) => {
+                counts.failed += 1;
+                eprintln!("failed   {}: {}", key, e);
+            }
+        }
+    }
+    Ok(counts)
+}
+
+fn cmd_export(backend: &Backend, args: &Args) -> Result<(), CliError> {
+    match args.option("format").unwrap_or("json") {
+        "json" => {
+            // The alias `go` resolves wins when several collections have it
+            let mut shortcuts = HashMap::new();
+            for entry in backend.list(args.option("collection"), None)? {
+                shortcuts.entry(entry.key).or_insert(entry.shortcut);
+            }
+            let data = shortcuts_json(&shortcuts).map_err(|e| format!("Failed to write JSON: {}", e))?;
+            println!("{}", data);
+        }
+        "bookmarks" => {
+            if args.option("collection").is_some() {
+                return Err(CliError::Usage("--collection only applies to --format json.".to_string()));
+            }
+            print!("{}", backend.export_bookmarks()?);
+        }
+        other => return Err(CliError::Usage(format!("Unsupported export format '{}'.", other))),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::CollectionConfig;
+    use crate::elements::shortcut::load_collections;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    // Answers every connection with `status` and an empty JSON list, keeping the requests
+    fn fake_server(status: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = [0u8; 4096];
+                let n = stream.read(&mut request).unwrap_or(0);
+                seen.lock().unwrap().push(String::from_utf8_lossy(&request[..n]).to_string());
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n[]", status);
+            }
+        });
+        (url, requests)
+    }
+
+    // A files backend whose only collection is `contents`, in a new file in the scratch directory
+    fn files_backend_with(name: &str, contents: &str) -> (Backend, String) {
+        config::ensure_data_dir().unwrap();
+        let file = config::data_path(&format!("{}.json", name));
+        fs::write(&file, contents).unwrap();
+        let state = crate::load_app_state();
+        let collection = CollectionConfig { name: name.to_string(), file: file.clone(), hidden: false, priority: None, directory: false };
+        *state.collections.lock().unwrap() = load_collections(&[collection]);
+        (Backend::Files(Box::new(state)), file)
+    }
+
+    fn shortcuts_of(backend: &Backend) -> HashMap<String, Shortcut> {
+        backend.list(None, None).unwrap().into_iter().map(|l| (l.key, l.shortcut)).collect()
+    }
+
+    #[test]
+    fn parses_options_switches_and_positionals() {
+        let args = parse_args(&strings(&["import", "--overwrite", "bookmarks.html", "--collection", "Work", "--tag=a=b", "--local"])).unwrap();
+        assert_eq!(args.positional, ["import", "bookmarks.html"]);
+        assert_eq!(args.option("collection"), Some("Work"));
+        assert_eq!(args.option("tag"), Some("a=b"));
+        assert!(args.switch("overwrite") && args.switch("local") && !args.switch("help"));
+        assert_eq!(args.required(1, "file"), Ok("bookmarks.html"));
+        assert_eq!(args.required(2, "alias"), Err("Missing <alias>.".to_string()));
+
+        // A switch never takes the next argument; an option without a value is an error
+        let args = parse_args(&strings(&["--overwrite", "--collection=", "x"])).unwrap();
+        assert_eq!((args.positional.as_slice(), args.option("collection")), (["x".to_string()].as_slice(), Some("")));
+        assert_eq!(parse_args(&strings(&["list", "--tag"])).err(), Some("--tag needs a value.".to_string()));
+    }
+
+    #[test]
+    fn reads_dates_in_options() {
+        let args = parse_args(&strings(&["add", "--active-from", "2024-02-29", "--expires", "2024-02-29 13:45"])).unwrap();
+        assert!(matches!(time_option(&args, "active-from"), Ok(1709164800)));
+        assert!(matches!(time_option(&args, "expires"), Ok(1709214300)));
+        assert!(matches!(time_option(&args, "not-given"), Ok(0)));
+
+        let args = parse_args(&strings(&["add", "--expires", "tomorrow"])).unwrap();
+        assert!(matches!(time_option(&args, "expires"), Err(CliError::Usage(m)) if m.starts_with("--expires takes a date")));
+    }
+
+    #[test]
+    fn uses_a_server_that_answers() {
+        let (url, requests) = fake_server("200 OK");
+        let args = parse_args(&strings(&["list", "--server", &format!("{}/", url), "--token", "secret"])).unwrap();
+        let Ok(Backend::Server(server)) = connect(&args) else { panic!("expected the server backend") };
+        assert_eq!((server.url.as_str(), server.token.as_str()), (url.as_str(), "secret"));
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("GET /api/shortcuts?collection= HTTP/1.1\r\n"), "{}", request);
+        assert!(request.contains("\r\nAuthorization: Bearer secret\r\n"));
+
+        // --local skips the server, even a named one
+        let args = parse_args(&strings(&["list", "--local", "--server", &url])).unwrap();
+        assert!(matches!(connect(&args), Ok(Backend::Files(_))));
+    }
+
+    #[test]
+    fn a_named_server_that_fails_is_an_error() {
+        let failure = |url: &str| match connect(&parse_args(&strings(&["list", "--server", url])).unwrap()) {
+            Err(CliError::Failed(message)) => message,
+            _ => panic!("expected an error for {}", url),
+        };
+
+        let (url, _) = fake_server("401 Unauthorized");
+        assert!(failure(&url).ends_with("requires authentication; pass --token or set GO_SERVICE_TOKEN."));
+        let (url, _) = fake_server("404 Not Found");
+        assert!(failure(&url).ends_with("answered with HTTP 404; is it a go_service instance?"));
+
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        assert!(failure(&format!("http://{}", addr)).starts_with("Could not reach"));
+    }
+
+    #[test]
+    fn import_skips_or_replaces_existing_aliases() {
+        let existing = r#"{
+            "gh": {"url": "https://github.com", "owner": "ana", "active_from": 1700000000, "expires_at": 1900000000},
+            "docs": "https://docs.rs"
+        }"#;
+        let (backend, _) = files_backend_with("cli-import", existing);
+        let bookmarks = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<DL><p>
+    <DT><A HREF="https://github.com/new" SHORTCUTURL="gh">GitHub</A>
+    <DT><A HREF="https://docs.rs" SHORTCUTURL="docs">Docs</A>
+    <DT><A HREF="https://crates.io" SHORTCUTURL="crates">Crates</A>
+</DL><p>"#;
+        let entries: Vec<(String, Shortcut)> = parse_bookmarks(bookmarks).into_iter().map(|b| (b.alias, b.shortcut)).collect();
+
+        let counts = import_entries(&backend, &entries, None, false).unwrap();
+        assert_eq!(counts, ImportCounts { added: 1, replaced: 0, skipped: 2, failed: 0 });
+        let shortcuts = shortcuts_of(&backend);
+        assert_eq!(shortcuts["gh"].url, "https://github.com");
+        assert_eq!(shortcuts["crates"].url, "https://crates.io");
+
+        let counts = import_entries(&backend, &entries, None, true).unwrap();
+        assert_eq!(counts, ImportCounts { added: 0, replaced: 3, skipped: 0, failed: 0 });
+        let gh = &shortcuts_of(&backend)["gh"];
+        assert_eq!(gh.url, "https://github.com/new");
+        assert_eq!(gh.description, "GitHub");
+        // The bookmark has no owner or schedule, so the existing ones stay
+        assert_eq!((gh.owner.as_str(), gh.active_from, gh.expires_at), ("ana", 1700000000, 1900000000));
+    }
+
+    #[test]
+    fn import_counts_failures_and_saves_to_the_file() {
+        let (backend, file) = files_backend_with("cli-import-failures", "{}");
+        let entries = vec![
+            ("ok".to_string(), Shortcut::new("https://ok.example")),
+            ("empty".to_string(), Shortcut::new("")),
+        ];
+
+        let counts = import_entries(&backend, &entries, Some("cli-import-failures"), false).unwrap();
+        assert_eq!(counts, ImportCounts { added: 1, replaced: 0, skipped: 0, failed: 1 });
+        assert!(import_entries(&backend, &entries, Some("Unknown"), false).unwrap().failed == 1);
+
+        let saved = parse_shortcuts(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(saved.keys().collect::<Vec<_>>(), ["ok"]);
+    }
+}
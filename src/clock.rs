@@ -0,0 +1,142 @@
+// Small date helpers shared by the shortcut features (no chrono dependency).
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SECONDS_IN_DAY: u64 = 86400;
+
+pub fn current_ts() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+// Converts days since the unix epoch into a (year, month, day) civil date.
+pub fn civil_from_days(days_since_epoch: i64) -> (i64, u32, u32) {
+    let days = days_since_epoch + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+// Formats unix seconds as YYYY-MM-DD (UTC)
+pub fn format_date(ts: u64) -> String {
+    let (y, m, d) = civil_from_days((ts / SECONDS_IN_DAY) as i64);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+// Formats unix seconds as YYYY-MM-DD HH:MM (UTC)
+pub fn format_datetime(ts: u64) -> String {
+    let second_of_day = ts % SECONDS_IN_DAY;
+    format!("{} {:02}:{:02}", format_date(ts), second_of_day / 3600, (second_of_day % 3600) / 60)
+}
+
+// ISO 8601 week-numbering year and week (weeks start on Monday; week 1 holds the first Thursday)
+pub fn iso_week(ts: u64) -> (i64, u32) {
+    let days = (ts / SECONDS_IN_DAY) as i64;
+    // 1970-01-01 was a Thursday; 0 = Monday
+    let weekday = (days + 3).rem_euclid(7);
+    let thursday = days - weekday + 3;
+    let (year, _, _) = civil_from_days(thursday);
+    let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+    (year, week as u32)
+}
+
+// Value for a `datetime-local` input (UTC); empty for 0 (not set)
+pub fn datetime_input_value(ts: u64) -> String {
+    if ts == 0 { String::new() } else { format_datetime(ts).replace(' ', "T") }
+}
+
+// Converts a (year, month, day) civil date into days since the unix epoch.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// Parses YYYY-MM-DD HH:MM (UTC) back into unix seconds. Also takes the `T` separator of
+// datetime-local inputs, seconds, or a date alone (midnight).
+pub fn parse_datetime(input: &str) -> Option<u64> {
+    let input = input.trim();
+    let (date, time) = input.split_once(['T', ' ']).unwrap_or((input, "00:00"));
+
+    let mut date_parts = date.split('-').map(|p| p.parse::<u32>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+    let mut time_parts = time.split(':').map(|p| p.parse::<u64>().ok());
+    let (hour, minute) = (time_parts.next()??, time_parts.next()??);
+    let second = time_parts.next().flatten().unwrap_or(0);
+
+    if date_parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let days = days_from_civil(year as i64, month, day);
+    // Catches days past the end of the month, like 2024-02-30
+    if days < 0 || civil_from_days(days) != (year as i64, month, day) {
+        return None;
+    }
+    Some(days as u64 * SECONDS_IN_DAY + hour * 3600 + minute * 60 + second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-25508), (1900, 3, 1));
+        assert_eq!(days_from_civil(2400, 3, 1), 157114);
+        // Every day for a few centuries, leap rules included
+        for days in -30000..160000 {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days, "{}-{}-{}", y, m, d);
+        }
+    }
+
+    #[test]
+    fn formats_dates_and_times() {
+        assert_eq!(format_date(1709214330), "2024-02-29");
+        assert_eq!(format_datetime(1709214330), "2024-02-29 13:45");
+        assert_eq!(datetime_input_value(1709214330), "2024-02-29T13:45");
+        assert_eq!(datetime_input_value(0), "");
+    }
+
+    #[test]
+    fn parses_dates_and_times() {
+        assert_eq!(parse_datetime("2024-02-29 13:45:30"), Some(1709214330));
+        assert_eq!(parse_datetime(" 2024-02-29T13:45 "), Some(1709214300));
+        assert_eq!(parse_datetime("2024-02-29"), Some(1709164800));
+        assert_eq!(parse_datetime(&datetime_input_value(1709214300)), Some(1709214300));
+
+        for bad in ["", "2024-02-30", "2023-02-29", "2024-13-01", "2024-04-31", "2024-01-01 24:00", "2024-01-01 10:60", "1969-12-31", "2024-01-01-01", "tomorrow"] {
+            assert_eq!(parse_datetime(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn iso_weeks_match_the_calendar() {
+        // Checked against Python's date.isocalendar()
+        let cases = [
+            (0, (1970, 1)),
+            (1230508800, (2009, 1)),   // 2008-12-29, a Monday in the next year's week 1
+            (1451520000, (2015, 53)),  // 2015-12-31
+            (1451606400, (2015, 53)),  // 2016-01-01, still in the last week of 2015
+            (1609372800, (2020, 53)),  // 2020-12-31
+            (1609632000, (2020, 53)),  // 2021-01-03, a Sunday
+            (1735516800, (2025, 1)),   // 2024-12-30
+            (1792195200, (2026, 42)),  // 2026-10-17
+        ];
+        for (ts, expected) in cases {
+            assert_eq!(iso_week(ts), expected, "{}", format_date(ts));
+            // Any time of the day is in the same week
+            assert_eq!(iso_week(ts + SECONDS_IN_DAY - 1), expected, "{}", format_datetime(ts + SECONDS_IN_DAY - 1));
+        }
+    }
+}
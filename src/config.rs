@@ -0,0 +1,343 @@
+// Where the server listens and where every store keeps its data.
+//
+// Settings are layered, later sources winning: built-in defaults, the JSON config file
+// (go_service.json in the working directory, or --config / GO_SERVICE_CONFIG), GO_SERVICE_*
+// environment variables, then command-line flags. Relative store paths are resolved against
+// the data directory, so several instances can run side by side with their own files.
+
+use serde::Deserialize;
+use std::{collections::HashMap, env, fs, io, path::Path, sync::OnceLock};
+
+pub static CONFIG_FILE: &str = "go_service.json";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind: String,
+    pub port: u16,
+    // Base directory for every store and shortcut file with a relative path
+    pub data_dir: String,
+    // Stylesheets and scripts served under /static; relative to the working directory
+    pub static_dir: String,
+    // Login password for the web pages; empty turns password login off
+    pub auth_password: String,
+    // Bearer token for scripts and the CLI; empty turns token access off
+    pub auth_token: String,
+    // With authentication on, still let anyone follow shortcut redirects
+    pub public_redirects: bool,
+    // How long a login lasts
+    pub session_days: u64,
+    // Passphrase for the /hidden page and locked shortcuts; empty keeps them locked
+    pub hidden_passphrase: String,
+    // How long an unlock of the hidden shortcuts lasts
+    pub hidden_unlock_minutes: u64,
+    // Request header a login proxy puts the user name in, for the `{user}` variable; empty
+    // (the default) leaves `{user}` to the query string
+    pub user_header: String,
+    // Extra `{name}` variables for shortcut URLs; GO_VAR_<NAME> environment variables add
+    // to (and override) the ones from the config file
+    pub variables: HashMap<String, String>,
+    pub files: StoreFiles,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind: "0.0.0.0".to_string(),
+            port: 80,
+            data_dir: ".".to_string(),
+            static_dir: "./static".to_string(),
+            auth_password: String::new(),
+            auth_token: String::new(),
+            public_redirects: false,
+            session_days: 30,
+            hidden_passphrase: String::new(),
+            hidden_unlock_minutes: 30,
+            user_header: String::new(),
+            variables: HashMap::new(),
+            files: StoreFiles::default(),
+        }
+    }
+}
+
+// File name of each store. Shortcut files themselves are listed in `collections`.
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreFiles {
+    pub collections: String,
+    pub go_settings: String,
+    pub rules: String,
+    pub stats: String,
+    pub health: String,
+    pub history: String,
+    pub notes: String,
+    pub board: String,
+    pub requests: String,
+    pub queries: String,
+    pub connections: String,
+    pub connections_key: String,
+    pub themes: String,
+    pub current_theme: String,
+}
+
+impl Default for StoreFiles {
+    fn default() -> Self {
+        StoreFiles {
+            collections: "collections.json".to_string(),
+            go_settings: "go_settings.json".to_string(),
+            rules: "shortcut-rules.json".to_string(),
+            stats: "shortcut-stats.json".to_string(),
+            health: "shortcut-health.json".to_string(),
+            history: "shortcut-history.jsonl".to_string(),
+            notes: "notes.json".to_string(),
+            board: "board.json".to_string(),
+            requests: "saved_requests.json".to_string(),
+            queries: "saved_queries.json".to_string(),
+            connections: "connections.json.enc".to_string(),
+            connections_key: "connections.key".to_string(),
+            themes: "themes.json".to_string(),
+            current_theme: "current_theme.json".to_string(),
+        }
+    }
+}
+
+// Store names as used in `files`; flags and env vars are derived from them
+// (`go_settings` -> --go-settings-file, GO_SERVICE_GO_SETTINGS_FILE)
+const STORES: &[&str] = &[
+    "collections", "go_settings", "rules", "stats", "health", "history", "notes", "board",
+    "requests", "queries", "connections", "connections_key", "themes", "current_theme",
+];
+
+// Settings other than the store files
+const SETTINGS: &[&str] = &[
+    "bind", "port", "data_dir", "static_dir", "auth_password", "auth_token", "public_redirects",
+    "session_days", "hidden_passphrase", "hidden_unlock_minutes", "user_header",
+];
+
+impl StoreFiles {
+    fn get_mut(&mut self, store: &str) -> Option<&mut String> {
+        Some(match store {
+            "collections" => &mut self.collections,
+            "go_settings" => &mut self.go_settings,
+            "rules" => &mut self.rules,
+            "stats" => &mut self.stats,
+            "health" => &mut self.health,
+            "history" => &mut self.history,
+            "notes" => &mut self.notes,
+            "board" => &mut self.board,
+            "requests" => &mut self.requests,
+            "queries" => &mut self.queries,
+            "connections" => &mut self.connections,
+            "connections_key" => &mut self.connections_key,
+            "themes" => &mut self.themes,
+            "current_theme" => &mut self.current_theme,
+            _ => return None,
+        })
+    }
+}
+
+impl Config {
+    // Applies one setting by name: one of SETTINGS, or `<store>_file`
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "bind" => self.bind = value.to_string(),
+            "port" => self.port = value.parse().map_err(|_| format!("Invalid port '{}'.", value))?,
+            "data_dir" => self.data_dir = value.to_string(),
+            "static_dir" => self.static_dir = value.to_string(),
+            "auth_password" => self.auth_password = value.to_string(),
+            "auth_token" => self.auth_token = value.to_string(),
+            "public_redirects" => {
+                self.public_redirects = match value {
+                    "true" | "1" | "yes" => true,
+                    "false" | "0" | "no" | "" => false,
+                    _ => return Err(format!("Invalid public_redirects '{}'; use true or false.", value)),
+                }
+            }
+            "session_days" => {
+                self.session_days = value.parse().map_err(|_| format!("Invalid session_days '{}'.", value))?
+            }
+            "hidden_passphrase" => self.hidden_passphrase = value.to_string(),
+            "hidden_unlock_minutes" => {
+                self.hidden_unlock_minutes =
+                    value.parse().map_err(|_| format!("Invalid hidden_unlock_minutes '{}'.", value))?
+            }
+            "user_header" => self.user_header = value.to_string(),
+            _ => {
+                let file = name
+                    .strip_suffix("_file")
+                    .and_then(|store| self.files.get_mut(store))
+                    .ok_or_else(|| format!("Unknown setting '{}'.", name))?;
+                *file = value.to_string();
+            }
+        }
+        Ok(())
+    }
+
+    // The store files with relative paths placed in the data directory
+    fn resolve_files(&mut self) {
+        for store in STORES {
+            if let Some(file) = self.files.get_mut(store) {
+                *file = join(&self.data_dir, file);
+            }
+        }
+    }
+
+    // Authentication is on as soon as a password or a token is configured
+    pub fn auth_enabled(&self) -> bool {
+        !self.auth_password.is_empty() || !self.auth_token.is_empty()
+    }
+
+    // Base URL of this instance as seen from the same machine, for the CLI
+    pub fn local_url(&self) -> String {
+        let host = match self.bind.as_str() {
+            "" | "0.0.0.0" | "::" | "[::]" => "127.0.0.1",
+            host => host,
+        };
+        if host.contains(':') && !host.starts_with('[') {
+            format!("http://[{}]:{}", host, self.port)
+        } else {
+            format!("http://{}:{}", host, self.port)
+        }
+    }
+}
+
+fn join(dir: &str, file: &str) -> String {
+    if dir.is_empty() || dir == "." {
+        return file.to_string();
+    }
+    Path::new(dir).join(file).to_string_lossy().into_owned()
+}
+
+fn env_name(setting: &str) -> String {
+    format!("GO_SERVICE_{}", setting.to_uppercase())
+}
+
+// Every setting name, including `<store>_file` for each store
+fn setting_names() -> impl Iterator<Item = String> {
+    SETTINGS.iter().map(|s| s.to_string()).chain(STORES.iter().map(|s| format!("{}_file", s)))
+}
+
+// Builds the configuration and returns it with the arguments that aren't configuration
+// flags (the command and its own options).
+pub fn load(args: &[String]) -> Result<(Config, Vec<String>), String> {
+    let mut flags: Vec<(String, String)> = Vec::new();
+    let mut config_file: Option<String> = env::var("GO_SERVICE_CONFIG").ok();
+    let mut rest = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            rest.push(arg.clone());
+            continue;
+        };
+        let (name, inline_value) = match flag.split_once('=') {
+            Some((name, value)) => (name.replace('-', "_"), Some(value.to_string())),
+            None => (flag.replace('-', "_"), None),
+        };
+        if name != "config" && !setting_names().any(|s| s == name) {
+            rest.push(arg.clone());
+            continue;
+        }
+        let value = match inline_value {
+            Some(value) => value,
+            None => iter.next().cloned().ok_or_else(|| format!("--{} needs a value.", flag))?,
+        };
+        if name == "config" {
+            config_file = Some(value);
+        } else {
+            flags.push((name, value));
+        }
+    }
+
+    // An explicitly named config file has to exist; the default one is optional
+    let mut config = match config_file {
+        Some(path) => read_config_file(&path)?,
+        None if Path::new(CONFIG_FILE).exists() => read_config_file(CONFIG_FILE)?,
+        None => Config::default(),
+    };
+
+    for name in setting_names() {
+        if let Ok(value) = env::var(env_name(&name)) {
+            config.set(&name, &value).map_err(|e| format!("{}: {}", env_name(&name), e))?;
+        }
+    }
+    for (name, value) in flags {
+        config.set(&name, &value)?;
+    }
+    for (name, value) in env::vars() {
+        if let Some(variable) = name.strip_prefix("GO_VAR_").filter(|v| !v.is_empty()) {
+            config.variables.insert(variable.to_lowercase(), value);
+        }
+    }
+
+    config.resolve_files();
+    Ok((config, rest))
+}
+
+fn read_config_file(path: &str) -> Result<Config, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Invalid config file {}: {}", path, e))
+}
+
+// Makes `config` the one every module reads. Only the first call has an effect.
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let mut config = Config::default();
+        // Tests that save (history, stats) write to a scratch directory, not the source tree
+        if cfg!(test) {
+            let dir = env::temp_dir().join(format!("go-service-test-{}", std::process::id()));
+            config.data_dir = dir.to_string_lossy().into_owned();
+        }
+        config.resolve_files();
+        config
+    })
+}
+
+pub fn files() -> &'static StoreFiles {
+    &config().files
+}
+
+// Creates the data directory on first start so the stores can be saved
+pub fn ensure_data_dir() -> io::Result<()> {
+    fs::create_dir_all(&config().data_dir)
+}
+
+// Places a relative shortcut file (from collections.json) in the data directory
+pub fn data_path(file: &str) -> String {
+    join(&config().data_dir, file)
+}
+
+// Flag help for `go_service help`
+pub fn usage() -> String {
+    let stores: Vec<String> = STORES.iter().map(|s| s.replace('_', "-")).collect();
+    let stores: Vec<String> = stores.chunks(5).map(|c| c.join(", ")).collect();
+    format!(
+        "Configuration (also settable in {} or as GO_SERVICE_* environment variables):
+  --config PATH                JSON config file (GO_SERVICE_CONFIG)
+  --bind ADDRESS               Address to listen on (default 0.0.0.0)
+  --port PORT                  Port to listen on (default 80)
+  --data-dir DIR               Directory for the data files (default .)
+  --static-dir DIR             Directory served under /static (default ./static)
+  --auth-password PASSWORD     Require a login for every page (prefer the file or
+                               GO_SERVICE_AUTH_PASSWORD over the flag)
+  --auth-token TOKEN           Accept `Authorization: Bearer TOKEN`; the CLI sends it too
+  --public-redirects true      Keep shortcut redirects open when authentication is on
+  --session-days DAYS          How long a login lasts (default 30)
+  --hidden-passphrase PHRASE   Unlocks /hidden and locked shortcuts (prefer the file or
+                               GO_SERVICE_HIDDEN_PASSPHRASE over the flag)
+  --hidden-unlock-minutes MIN  How long an unlock lasts (default 30)
+  --user-header NAME           Header with the signed-in user from a login proxy, used
+                               by the {{user}} variable (e.g. X-Forwarded-User; off
+                               by default)
+  --<store>-file PATH          Override one store's file, relative to the data directory.
+                               Stores: {}",
+        CONFIG_FILE,
+        stores.join(",\n                               ")
+    )
+}
@@ -0,0 +1,191 @@
+// Cross-site request forgery protection for every state-changing request.
+//
+// Each browser gets a random token in the `go_csrf` cookie. The script added by
+// `render_base_page` copies it into a hidden `_csrf` field of every POST form and an
+// `X-CSRF-Token` header on same-origin `fetch` calls, and the middleware below checks it
+// matches the cookie, which another site can neither read nor set. On top of that, any request
+// whose Origin (or Referer) names another site is refused, which is what protects the JSON
+// endpoints.
+//
+// Requests without Origin, Referer or cookie come from scripts (curl, the CLI), not browsers,
+// and requests with an API token don't rely on cookies, so neither needs a token.
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    cookie::{time::Duration as CookieDuration, Cookie, SameSite},
+    dev::{self, ServiceRequest, ServiceResponse},
+    http::{header, Method},
+    middleware::Next,
+    web, Error, HttpRequest, HttpResponse,
+};
+use rand::RngCore;
+
+use crate::auth::{constant_time_eq, has_valid_token};
+use crate::resolve::parse_query;
+
+pub static CSRF_COOKIE: &str = "go_csrf";
+pub static CSRF_HEADER: &str = "X-CSRF-Token";
+pub static CSRF_FIELD: &str = "_csrf";
+
+// Largest form body accepted anywhere (also the FormConfig limit in main.rs)
+pub const FORM_LIMIT: usize = 8 * 1024 * 1024;
+
+pub async fn verify_csrf(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let cookie_token = req.cookie(CSRF_COOKIE).map(|c| c.value().to_string());
+
+    if is_state_changing(req.method()) && !has_valid_token(req.request()) {
+        if let Some(response) = check_origin(req.request()) {
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+
+        let from_browser = cookie_token.is_some()
+            || req.headers().contains_key(header::ORIGIN)
+            || req.headers().contains_key(header::REFERER);
+        if from_browser && !is_json(req.request()) {
+            let submitted = match header_token(req.request()) {
+                Some(token) => Some(token),
+                None => match form_token(&mut req).await {
+                    Ok(token) => token,
+                    Err(response) => return Ok(req.into_response(response).map_into_right_body()),
+                },
+            };
+            let valid = matches!((&cookie_token, &submitted), (Some(c), Some(s)) if constant_time_eq(c, s));
+            if !valid {
+                let response = HttpResponse::Forbidden()
+                    .body("Missing or outdated security token. Reload the page and try again.");
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+        }
+    }
+
+    let is_https = req.connection_info().scheme() == "https";
+    let mut res = next.call(req).await?;
+
+    // Hand out the token with the first page a browser loads
+    if cookie_token.is_none() && res.request().method() == Method::GET {
+        let cookie = Cookie::build(CSRF_COOKIE, new_token())
+            .path("/")
+            .same_site(SameSite::Strict)
+            .secure(is_https)
+            .max_age(CookieDuration::days(365))
+            .finish();
+        let _ = res.response_mut().add_cookie(&cookie);
+    }
+    Ok(res.map_into_left_body())
+}
+
+fn is_state_changing(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn is_json(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+// Rejects requests that a page on another site sent. Returns the rejection, if any.
+fn check_origin(req: &HttpRequest) -> Option<HttpResponse> {
+    let info = req.connection_info();
+    let own = format!("{}://{}", info.scheme(), info.host());
+    let header_value = |name| req.headers().get(name).and_then(|v: &header::HeaderValue| v.to_str().ok());
+
+    let same_site = match (header_value(header::ORIGIN), header_value(header::REFERER)) {
+        (Some(origin), _) => origin == own,
+        (None, Some(referer)) => referer == own || referer.starts_with(&format!("{}/", own)),
+        (None, None) => true,
+    };
+    if same_site {
+        None
+    } else {
+        Some(HttpResponse::Forbidden().body("Cross-site requests are not allowed."))
+    }
+}
+
+fn header_token(req: &HttpRequest) -> Option<String> {
+    req.headers().get(CSRF_HEADER).and_then(|v| v.to_str().ok()).map(String::from)
+}
+
+// Reads `_csrf` from a url-encoded form body, then puts the body back for the handler
+async fn form_token(req: &mut ServiceRequest) -> Result<Option<String>, HttpResponse> {
+    let is_form = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+    if !is_form {
+        return Ok(None);
+    }
+
+    let payload = req
+        .extract::<web::Payload>()
+        .await
+        .map_err(|_| HttpResponse::BadRequest().body("Failed to read the request body."))?;
+    let body = match payload.to_bytes_limited(FORM_LIMIT).await {
+        Ok(Ok(body)) => body,
+        Ok(Err(_)) => return Err(HttpResponse::BadRequest().body("Failed to read the request body.")),
+        Err(_) => return Err(HttpResponse::PayloadTooLarge().body("The form is too large.")),
+    };
+
+    let token = std::str::from_utf8(&body)
+        .ok()
+        .and_then(|b| parse_query(b).ok())
+        .and_then(|pairs| pairs.into_iter().find(|(k, _)| k == CSRF_FIELD).map(|(_, v)| v));
+    req.set_payload(dev::Payload::from(body));
+    Ok(token)
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Added to every page by `render_base_page`
+pub fn csrf_script() -> String {
+    format!(
+        r#"<script>
+    (function () {{
+        // Copies the {cookie} cookie into POST forms and same-origin fetch calls (see csrf.rs)
+        function csrfToken() {{
+            const match = document.cookie.match(/(?:^|;\s*){cookie}=([^;]+)/);
+            return match ? decodeURIComponent(match[1]) : '';
+        }}
+        function addToken(form) {{
+            if ((form.getAttribute('method') || '').toLowerCase() !== 'post') return;
+            let input = form.querySelector('input[name="{field}"]');
+            if (!input) {{
+                input = document.createElement('input');
+                input.type = 'hidden';
+                input.name = '{field}';
+                form.appendChild(input);
+            }}
+            input.value = csrfToken();
+        }}
+        // Forms submitted from script (form.submit()) skip the submit event, so add it up front too
+        document.addEventListener('DOMContentLoaded', () => document.querySelectorAll('form').forEach(addToken));
+        document.addEventListener('submit', (event) => addToken(event.target), true);
+
+        const originalFetch = window.fetch;
+        window.fetch = function (resource, init) {{
+            init = init || {{}};
+            const method = (init.method || (resource instanceof Request ? resource.method : 'GET')).toUpperCase();
+            const url = new URL(resource instanceof Request ? resource.url : resource, location.href);
+            if (method !== 'GET' && method !== 'HEAD' && url.origin === location.origin) {{
+                const headers = new Headers(init.headers || (resource instanceof Request ? resource.headers : {{}}));
+                headers.set('{header}', csrfToken());
+                init.headers = headers;
+            }}
+            return originalFetch.call(this, resource, init);
+        }};
+    }})();
+    </script>"#,
+        cookie = CSRF_COOKIE,
+        field = CSRF_FIELD,
+        header = CSRF_HEADER
+    )
+}
@@ -1,29 +1,74 @@
 use actix_web::{
+    http::StatusCode,
     post,
     web::{Data, Form}, 
-    HttpResponse, Responder,
+    HttpRequest, HttpResponse, Responder,
 };
 use serde::Deserialize;
 use std::{
     collections::HashMap,
     fs,
     io,
+    path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
+    time::SystemTime,
 };
 
-use crate::app_state::AppState;
+use crate::app_state::{AppState, CollectionConfig, GoSettings, Shortcut, ShortcutCollection};
+use crate::clock::{current_ts, parse_datetime};
+use crate::config::{data_path, files};
+use crate::hidden::{guard_hidden, hidden_taken};
+use crate::history::{self, actor, Change};
+use crate::resolve::{ResolveError, Resolver};
+use crate::stats;
+use crate::variables::VariableContext;
 
-// File constants
+// Default shortcut files, relative to the data directory (see config.rs)
 static SHORTCUTS_FILE: &str = "shortcuts.json";
 static HIDDEN_SHORTCUTS_FILE: &str = "hidden-shortcuts.json";
 static WORK_SHORTCUTS_FILE: &str = "work-shortcuts.json"; // Added constant for work shortcuts file
+static SHORTCUTS_DIR: &str = "shortcuts.d"; // Team fragments, merged in filename order
 
 // Struct to capture the shortcut form data
 #[derive(Deserialize)]
 pub struct AddShortcutForm {
     pub shortcut: String,
     pub url: String,
+    // Name of the target collection; older forms only send the `hidden` checkbox
+    pub collection: Option<String>,
     pub hidden: Option<String>,
+    #[serde(flatten)]
+    pub details: ShortcutDetailsForm,
+}
+
+// Optional metadata fields shared by the add and edit forms
+#[derive(Deserialize)]
+pub struct ShortcutDetailsForm {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: String, // Comma separated
+    #[serde(default)]
+    pub owner: String,
+    #[serde(default)]
+    pub notes: String,
+    // `datetime-local` values in UTC; empty clears the limit, a missing field keeps it
+    #[serde(default)]
+    pub active_from: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+// Struct to capture the edit form: `original_key` is the alias being edited,
+// `shortcut` its (possibly new) name
+#[derive(Deserialize)]
+pub struct EditShortcutForm {
+    pub original_key: String,
+    pub shortcut: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub details: ShortcutDetailsForm,
 }
 
 // Struct to capture the key for deletion
@@ -32,48 +77,617 @@ pub struct DeleteShortcutForm {
     pub key: String,
 }
 
-// Helper function to save shortcuts back to JSON file
-fn save_shortcuts(path: &str, shortcuts: &HashMap<String, String>) -> io::Result<()> {
-    // Use serde_json::to_string_pretty for readable JSON
-    let data = serde_json::to_string_pretty(shortcuts)?;
+// Struct to capture the shortcut settings form data
+#[derive(Deserialize)]
+pub struct ShortcutSettingsForm {
+    pub template_fallback_url: String,
+    pub inactive_fallback_url: Option<String>,
+    // Numbers arrive as text so a cleared field means "the default" instead of a rejected form
+    pub health_check_interval_secs: Option<String>,
+    pub health_check_concurrency: Option<String>,
+    pub health_check_host_delay_ms: Option<String>,
+    pub health_check_timeout_secs: Option<String>,
+}
+
+// The collections used when no collections.json exists: the three original files, in the
+// order they have always been resolved, followed by the shortcuts.d fragment directory.
+pub fn default_collections() -> Vec<CollectionConfig> {
+    vec![
+        CollectionConfig { name: "Shortcuts".to_string(), file: SHORTCUTS_FILE.to_string(), hidden: false, priority: Some(1), directory: false },
+        CollectionConfig { name: "Hidden".to_string(), file: HIDDEN_SHORTCUTS_FILE.to_string(), hidden: true, priority: Some(2), directory: false },
+        CollectionConfig { name: "Work".to_string(), file: WORK_SHORTCUTS_FILE.to_string(), hidden: false, priority: Some(3), directory: false },
+        CollectionConfig { name: "Team".to_string(), file: SHORTCUTS_DIR.to_string(), hidden: false, priority: Some(4), directory: true },
+    ]
+}
+
+// Load the list of collections (name, file, hidden) from collections.json
+pub fn load_collections_config(path: &str) -> io::Result<Vec<CollectionConfig>> {
+    let data = fs::read_to_string(path)?;
+    let configs: Vec<CollectionConfig> = serde_json::from_str(&data)?;
+    Ok(configs)
+}
+
+// Loads every configured collection and sorts them into resolution order
+// (ascending priority; ties and missing priorities keep their place in the list)
+pub fn load_collections(configs: &[CollectionConfig]) -> Vec<ShortcutCollection> {
+    let mut collections: Vec<ShortcutCollection> = configs
+        .iter()
+        .enumerate()
+        .map(|(index, config)| {
+            let priority = config.priority.unwrap_or(index as i32 + 1);
+            let mut collection = ShortcutCollection::new(config, priority, HashMap::new());
+            collection.file = data_path(&config.file);
+            collection.modified = file_modified(&collection.file);
+            match read_collection(&collection.file, config.directory) {
+                Ok((shortcuts, sources)) => {
+                    collection.shortcuts = shortcuts;
+                    collection.sources = sources;
+                }
+                Err(e) => {
+                    eprintln!("Failed to load {}: {e}", collection.file);
+                    // A file that doesn't exist yet is just an empty collection
+                    if collection.modified.is_some() {
+                        collection.load_error = Some(e);
+                    }
+                }
+            }
+            collection
+        })
+        .collect();
+    collections.sort_by_key(|c| c.priority);
+    collections
+}
+
+// Reads a shortcuts file and checks every entry is usable
+fn read_shortcuts_file(path: &str) -> Result<HashMap<String, Shortcut>, String> {
+    let shortcuts = load_shortcuts(path).map_err(|e| e.to_string())?;
+    if let Some(key) = shortcuts
+        .iter()
+        .find(|(k, s)| k.trim().is_empty() || s.url.trim().is_empty())
+        .map(|(k, _)| k)
+    {
+        return Err(format!("Shortcut '{}' has an empty alias or URL", key));
+    }
+    Ok(shortcuts)
+}
+
+// Shortcuts of a collection plus, for fragment directories, the file each alias came from
+pub type CollectionContents = (HashMap<String, Shortcut>, HashMap<String, String>);
+
+// Reads a collection's file, or every `*.json` fragment of a directory collection in filename
+// order. A later fragment overrides an alias from an earlier one.
+pub fn read_collection(path: &str, directory: bool) -> Result<CollectionContents, String> {
+    if !directory {
+        return Ok((read_shortcuts_file(path)?, HashMap::new()));
+    }
+
+    let mut shortcuts = HashMap::new();
+    let mut sources = HashMap::new();
+    for fragment in fragment_files(path).map_err(|e| e.to_string())? {
+        let fragment = fragment.to_string_lossy().to_string();
+        let loaded = read_shortcuts_file(&fragment).map_err(|e| format!("{}: {}", fragment, e))?;
+        for (key, shortcut) in loaded {
+            sources.insert(key.clone(), fragment.clone());
+            shortcuts.insert(key, shortcut);
+        }
+    }
+    Ok((shortcuts, sources))
+}
+
+fn fragment_files(dir: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// Last modification of a shortcut file; for a directory, the newest of it and its fragments
+pub fn file_modified(path: &str) -> Option<SystemTime> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    if !Path::new(path).is_dir() {
+        return Some(modified);
+    }
+    fragment_files(path)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .chain(std::iter::once(modified))
+        .max()
+}
+
+// Logs aliases that exist in several collections; only the highest priority one resolves
+pub fn log_collisions(collections: &[ShortcutCollection]) {
+    for collision in find_collisions(collections) {
+        eprintln!(
+            "Shortcut '{}' in {} is shadowed by {}",
+            collision.key,
+            collision.shadowed.join(", "),
+            collision.winner
+        );
+    }
+}
+
+// An alias defined in more than one collection. Only `winner` is reachable.
+pub struct Collision {
+    pub key: String,
+    pub winner: String,
+    pub shadowed: Vec<String>,
+}
+
+// Lists every alias that exists in more than one collection, with the collection that
+// wins resolution and the ones it shadows
+pub fn find_collisions(collections: &[ShortcutCollection]) -> Vec<Collision> {
+    let mut owners: HashMap<&str, Vec<&str>> = HashMap::new();
+    for c in collections {
+        for key in c.shortcuts.keys() {
+            owners.entry(key.as_str()).or_default().push(c.name.as_str());
+        }
+    }
+
+    let mut collisions: Vec<Collision> = owners
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(key, names)| Collision {
+            key: key.to_string(),
+            winner: names[0].to_string(),
+            shadowed: names[1..].iter().map(|n| n.to_string()).collect(),
+        })
+        .collect();
+    collisions.sort_by(|a, b| a.key.cmp(&b.key));
+    collisions
+}
+
+// Resolves `key` the way `go` would and reports a `go:` chain that cycles or runs too deep.
+// A chain that ends at a missing alias is allowed; that alias may be added later.
+pub fn check_chain(state: &AppState, collections: &[ShortcutCollection], key: &str) -> Result<(), String> {
+    let rules = state.rules.lock().unwrap();
+    let settings = state.go_settings.lock().unwrap();
+    let resolver = Resolver { collections, rules: &rules, settings: &settings, variables: VariableContext::default() };
+    match resolver.resolve(key, &mut Vec::new()) {
+        Ok(_) | Err(ResolveError::NotFound) => Ok(()),
+        Err(e) => Err(e.describe()),
+    }
+}
+
+// Finds an alias in the first collection (in resolution order) that has it
+pub fn find_shortcut<'a>(collections: &'a [ShortcutCollection], key: &str) -> Option<(&'a ShortcutCollection, &'a Shortcut)> {
+    collections
+        .iter()
+        .find_map(|c| c.shortcuts.get(key).map(|s| (c, s)))
+}
+
+// Like find_shortcut, but skips entries outside their active window at `now`
+pub fn find_active_shortcut<'a>(
+    collections: &'a [ShortcutCollection],
+    key: &str,
+    now: u64,
+) -> Option<(&'a ShortcutCollection, &'a Shortcut)> {
+    collections
+        .iter()
+        .find_map(|c| c.shortcuts.get(key).filter(|s| s.is_active(now)).map(|s| (c, s)))
+}
+
+// All shortcuts from non-hidden collections, merged the way `go` resolves them
+// (an alias in an earlier collection wins over the same alias in a later one)
+pub fn visible_shortcuts(collections: &[ShortcutCollection]) -> HashMap<String, Shortcut> {
+    let mut combined = HashMap::new();
+    for c in collections.iter().rev().filter(|c| !c.hidden) {
+        combined.extend(c.shortcuts.clone());
+    }
+    combined
+}
+
+// Load shortcut resolution settings
+pub fn load_go_settings(path: &str) -> io::Result<GoSettings> {
+    let data = fs::read_to_string(path)?;
+    let settings: GoSettings = serde_json::from_str(&data)?;
+    Ok(settings)
+}
+
+// Save shortcut resolution settings
+fn save_go_settings(path: &str, settings: &GoSettings) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(settings)?;
     fs::write(path, data)
 }
 
+// Load a shortcuts file. Each entry is either the simple `"alias": "url"` form
+// or a full Shortcut object, so older files keep working.
+pub fn load_shortcuts(path: &str) -> io::Result<HashMap<String, Shortcut>> {
+    let data = fs::read_to_string(path)?;
+    parse_shortcuts(&data)
+}
+
+pub fn parse_shortcuts(data: &str) -> io::Result<HashMap<String, Shortcut>> {
+    let raw: HashMap<String, serde_json::Value> = serde_json::from_str(data)?;
+    raw.into_iter()
+        .map(|(key, value)| {
+            let shortcut = match value {
+                serde_json::Value::String(url) => Shortcut::new(&url),
+                other => serde_json::from_value(other)?,
+            };
+            Ok((key, shortcut))
+        })
+        .collect()
+}
+
+// Helper function to save shortcuts back to JSON file.
+// Shortcuts without any metadata are written in the simple `"alias": "url"` form.
+pub fn save_shortcuts(path: &str, shortcuts: &HashMap<String, Shortcut>) -> io::Result<()> {
+    fs::write(path, shortcuts_json(shortcuts)?)
+}
+
+// The contents of a shortcuts file, sorted by alias
+pub fn shortcuts_json(shortcuts: &HashMap<String, Shortcut>) -> serde_json::Result<String> {
+    let mut keys: Vec<&String> = shortcuts.keys().collect();
+    keys.sort();
+
+    let mut map = serde_json::Map::new();
+    for key in keys {
+        let shortcut = &shortcuts[key];
+        let value = if shortcut.is_plain() {
+            serde_json::Value::String(shortcut.url.clone())
+        } else {
+            serde_json::to_value(shortcut)?
+        };
+        map.insert(key.clone(), value);
+    }
+
+    // Use serde_json::to_string_pretty for readable JSON
+    serde_json::to_string_pretty(&map)
+}
+
+// Splits the comma separated tag input into a clean, de-duplicated list
+fn parse_tags(input: &str) -> Vec<String> {
+    clean_tags(input.split(','))
+}
+
+fn clean_tags<'a>(tags: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags.map(str::trim).filter(|t| !t.is_empty()) {
+        if !cleaned.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+// The editable fields of a shortcut, as submitted by a form or the JSON API
+pub struct ShortcutInput {
+    pub url: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub owner: String,
+    pub notes: String,
+    // Only the /hidden page sets the lock; None keeps what the shortcut already has
+    pub locked: Option<bool>,
+    // Unix seconds (0 for no limit); None keeps what the shortcut already has
+    pub active_from: Option<u64>,
+    pub expires_at: Option<u64>,
+}
+
+impl ShortcutInput {
+    pub fn new(url: &str, description: &str, tags: &[String], owner: &str, notes: &str) -> Self {
+        ShortcutInput {
+            url: url.trim().to_string(),
+            description: description.trim().to_string(),
+            tags: clean_tags(tags.iter().map(String::as_str)),
+            owner: owner.trim().to_string(),
+            notes: notes.trim().to_string(),
+            locked: None,
+            active_from: None,
+            expires_at: None,
+        }
+    }
+
+    pub fn from_form(details: &ShortcutDetailsForm, url: &str) -> Result<Self, ShortcutError> {
+        let mut input = Self::new(url, &details.description, &parse_tags(&details.tags), &details.owner, &details.notes);
+        input.active_from = parse_form_time(details.active_from.as_deref(), "active_from")?;
+        input.expires_at = parse_form_time(details.expires_at.as_deref(), "expires_at")?;
+        Ok(input)
+    }
+
+    // The active window after applying the input to `existing`
+    fn window(&self, existing: Option<&Shortcut>) -> (u64, u64) {
+        (
+            self.active_from.unwrap_or(existing.map_or(0, |s| s.active_from)),
+            self.expires_at.unwrap_or(existing.map_or(0, |s| s.expires_at)),
+        )
+    }
+
+    // Builds the stored shortcut, keeping the creation time of an entry it replaces
+    fn into_shortcut(self, existing: Option<&Shortcut>) -> Shortcut {
+        let now = current_ts();
+        let (active_from, expires_at) = self.window(existing);
+        Shortcut {
+            url: self.url,
+            description: self.description,
+            tags: self.tags,
+            owner: self.owner,
+            notes: self.notes,
+            created_at: existing.map(|s| s.created_at).filter(|t| *t != 0).unwrap_or(now),
+            updated_at: now,
+            locked: self.locked.unwrap_or(existing.is_some_and(|s| s.locked)),
+            active_from,
+            expires_at,
+        }
+    }
+}
+
+// Reads an optional date and time field; an empty value means no limit
+fn parse_form_time(value: Option<&str>, field: &'static str) -> Result<Option<u64>, ShortcutError> {
+    match value.map(str::trim) {
+        None => Ok(None),
+        Some("") => Ok(Some(0)),
+        Some(text) => parse_datetime(text)
+            .map(Some)
+            .ok_or_else(|| invalid(field, "Use a date and time like 2024-05-01 09:00 (UTC).")),
+    }
+}
+
+// A shortcut that expires must do so after it starts
+fn check_window(input: &ShortcutInput, existing: Option<&Shortcut>) -> Result<(), ShortcutError> {
+    let (active_from, expires_at) = input.window(existing);
+    if expires_at != 0 && expires_at <= active_from {
+        return Err(invalid("expires_at", "The expiry has to be after the start."));
+    }
+    Ok(())
+}
+
+// Why a change to the shortcuts was refused
+pub enum ShortcutError {
+    // A submitted field is missing or unusable
+    Invalid { field: &'static str, message: String },
+    NotFound(String),
+    Conflict(String),
+    // The alias lives in a fragment directory
+    ReadOnly(String),
+    SaveFailed,
+}
+
+impl ShortcutError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ShortcutError::Invalid { .. } => StatusCode::BAD_REQUEST,
+            ShortcutError::NotFound(_) => StatusCode::NOT_FOUND,
+            ShortcutError::Conflict(_) => StatusCode::CONFLICT,
+            ShortcutError::ReadOnly(_) => StatusCode::FORBIDDEN,
+            ShortcutError::SaveFailed => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ShortcutError::Invalid { message, .. }
+            | ShortcutError::NotFound(message)
+            | ShortcutError::Conflict(message)
+            | ShortcutError::ReadOnly(message) => message.clone(),
+            ShortcutError::SaveFailed => "Failed to save shortcut.".to_string(),
+        }
+    }
+
+    // Plain text response used by the HTML form handlers
+    pub fn into_response(self) -> HttpResponse {
+        HttpResponse::build(self.status()).body(self.message())
+    }
+}
+
+fn invalid(field: &'static str, message: &str) -> ShortcutError {
+    ShortcutError::Invalid { field, message: message.to_string() }
+}
+
+fn read_only(collection: &ShortcutCollection, key: &str) -> ShortcutError {
+    let source = collection.sources.get(key).unwrap_or(&collection.file);
+    ShortcutError::ReadOnly(format!("Shortcut '{}' comes from {}, which is read-only.", key, source))
+}
+
+// Writes the collection back to its file. Refuses when the file changed on disk since it was
+// last loaded (an edit or sync the watcher hasn't picked up yet), so that edit isn't overwritten.
+pub fn save_collection(collection: &mut ShortcutCollection) -> Result<(), ShortcutError> {
+    let modified = file_modified(&collection.file);
+    if modified.is_some() && modified != collection.modified {
+        return Err(ShortcutError::Conflict(format!(
+            "{} was changed on disk since it was loaded. Try again in a moment.",
+            collection.file
+        )));
+    }
+    save_shortcuts(&collection.file, &collection.shortcuts).map_err(|e| {
+        eprintln!("Failed to save {}: {}", collection.file, e);
+        ShortcutError::SaveFailed
+    })?;
+    // Our own write isn't a change for the watcher to reload
+    collection.modified = file_modified(&collection.file);
+    Ok(())
+}
+
+// Adds `key` to the collection at `index`, saves its file and logs the change as made by `who`
+pub fn create_shortcut(
+    state: &AppState,
+    collections: &mut [ShortcutCollection],
+    index: usize,
+    key: &str,
+    input: ShortcutInput,
+    who: &str,
+) -> Result<(), ShortcutError> {
+    if key.is_empty() {
+        return Err(invalid("key", "Shortcut cannot be empty."));
+    }
+    if input.url.is_empty() {
+        return Err(invalid("url", "URL cannot be empty."));
+    }
+    if collections[index].directory {
+        return Err(ShortcutError::ReadOnly(format!(
+            "{} is read-only; add the shortcut to a file in {} instead.",
+            collections[index].name, collections[index].file
+        )));
+    }
+
+    // Adding an alias that another collection already has would leave one of them
+    // unreachable, so point the user at the existing entry instead
+    if let Some(other) = collections
+        .iter()
+        .enumerate()
+        .find(|(i, c)| *i != index && c.shortcuts.contains_key(key))
+        .map(|(_, c)| c)
+    {
+        return Err(ShortcutError::Conflict(format!(
+            "Shortcut '{}' already exists in {}. Edit or delete it there instead.",
+            key, other.name
+        )));
+    }
+
+    check_window(&input, collections[index].shortcuts.get(key))?;
+    // Kept so a rejected chain or a failed save leaves memory matching the file
+    let snapshot = collections[index].shortcuts.clone();
+    let record = input.into_shortcut(collections[index].shortcuts.get(key));
+    collections[index].shortcuts.insert(key.to_string(), record);
+
+    // Reject `go:` targets that would loop back on themselves
+    let saved = check_chain(state, collections, key)
+        .map_err(|e| invalid("url", &e))
+        .and_then(|()| save_collection(&mut collections[index]));
+    if let Err(e) = saved {
+        collections[index].shortcuts = snapshot;
+        return Err(e);
+    }
+
+    let collection = &collections[index];
+    history::record(&Change::new(who, &collection.name, key, snapshot.get(key), collection.shortcuts.get(key)));
+    Ok(())
+}
+
+// Replaces the URL and details of `original_key`, renaming it to `new_key`. The entry `go`
+// resolves is edited (the first collection that has the alias) and stays in that collection.
+pub fn update_shortcut(
+    state: &AppState,
+    collections: &mut [ShortcutCollection],
+    original_key: &str,
+    new_key: &str,
+    input: ShortcutInput,
+    who: &str,
+) -> Result<(), ShortcutError> {
+    if new_key.is_empty() {
+        return Err(invalid("key", "Shortcut cannot be empty."));
+    }
+    if input.url.is_empty() {
+        return Err(invalid("url", "URL cannot be empty."));
+    }
+
+    let Some(index) = collections.iter().position(|c| c.shortcuts.contains_key(original_key)) else {
+        return Err(ShortcutError::NotFound(format!("Shortcut '{}' does not exist.", original_key)));
+    };
+    if collections[index].directory {
+        return Err(read_only(&collections[index], original_key));
+    }
+
+    // A rename must not collide with a key in *any* collection
+    if new_key != original_key
+        && let Some((other, _)) = find_shortcut(collections, new_key) {
+        return Err(ShortcutError::Conflict(format!("Shortcut '{}' already exists in {}.", new_key, other.name)));
+    }
+
+    check_window(&input, collections[index].shortcuts.get(original_key))?;
+    // Kept so a rejected chain or a failed save leaves memory matching the file
+    let snapshot = collections[index].shortcuts.clone();
+    let existing = collections[index].shortcuts.remove(original_key);
+    let record = input.into_shortcut(existing.as_ref());
+    collections[index].shortcuts.insert(new_key.to_string(), record);
+
+    // Reject `go:` targets that would loop back on themselves, restoring the old entry
+    let saved = check_chain(state, collections, new_key)
+        .map_err(|e| invalid("url", &e))
+        .and_then(|()| save_collection(&mut collections[index]));
+    if let Err(e) = saved {
+        collections[index].shortcuts = snapshot;
+        return Err(e);
+    }
+
+    let collection = &collections[index];
+    let mut change = Change::new(who, &collection.name, new_key, existing.as_ref(), collection.shortcuts.get(new_key));
+    change.renamed_from = (new_key != original_key).then(|| original_key.to_string());
+    history::record(&change);
+
+    // Keep the usage history with the alias when it is renamed
+    if new_key != original_key {
+        stats::rename_alias(state, original_key, new_key);
+    }
+    Ok(())
+}
+
+// Deletes the alias from every writable collection that has it, saving each file that changed
+pub fn remove_shortcut(collections: &mut [ShortcutCollection], key: &str, who: &str) -> Result<(), ShortcutError> {
+    if key.is_empty() {
+        return Err(invalid("key", "Shortcut key cannot be empty."));
+    }
+
+    let mut deleted = false;
+    for collection in collections.iter_mut().filter(|c| !c.directory && c.shortcuts.contains_key(key)) {
+        remove_from_collection(collection, key, who)?;
+        deleted = true;
+    }
+
+    if !deleted {
+        return match collections.iter().find(|c| c.shortcuts.contains_key(key)) {
+            Some(c) => Err(read_only(c, key)),
+            None => Err(ShortcutError::NotFound(format!("Shortcut '{}' does not exist.", key))),
+        };
+    }
+    Ok(())
+}
+
+// Deletes the alias from this collection only and saves its file
+pub fn remove_from_collection(collection: &mut ShortcutCollection, key: &str, who: &str) -> Result<(), ShortcutError> {
+    if collection.directory && collection.shortcuts.contains_key(key) {
+        return Err(read_only(collection, key));
+    }
+    let Some(old) = collection.shortcuts.remove(key) else {
+        return Err(ShortcutError::NotFound(format!("Shortcut '{}' does not exist in {}.", key, collection.name)));
+    };
+    if let Err(e) = save_collection(collection) {
+        collection.shortcuts.insert(key.to_string(), old);
+        return Err(e);
+    }
+    history::record(&Change::new(who, &collection.name, key, Some(&old), None));
+    Ok(())
+}
+
 // Handler for the new shortcut form
 #[post("/add_shortcut")]
 pub async fn add_shortcut(
+    req: HttpRequest,
     form: Form<AddShortcutForm>,
     state: Data<Arc<AppState>>,
 ) -> impl Responder {
-    let is_hidden = form.hidden.is_some();
-    let shortcut = form.shortcut.trim();
-    let url = form.url.trim();
-
-    // Basic validation
-    if shortcut.is_empty() || url.is_empty() {
-        return HttpResponse::BadRequest().body("Shortcut and URL cannot be empty.");
-    }
-
-    if is_hidden {
-        // Add to hidden shortcuts
-        let mut hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
-        hidden_shortcuts.insert(shortcut.to_string(), url.to_string());
-        
-        // Persist to disk
-        if let Err(e) = save_shortcuts(HIDDEN_SHORTCUTS_FILE, &hidden_shortcuts) {
-            eprintln!("Failed to save hidden shortcuts: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to save hidden shortcut.");
-        }
-    } else {
-        // Add to visible shortcuts (using the general 'shortcuts.json' as the default visible file)
-        let mut shortcuts = state.shortcuts.lock().unwrap();
-        shortcuts.insert(shortcut.to_string(), url.to_string());
-
-        // Persist to disk
-        if let Err(e) = save_shortcuts(SHORTCUTS_FILE, &shortcuts) {
-            eprintln!("Failed to save shortcuts: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to save shortcut.");
+    let mut collections = state.collections.lock().unwrap();
+    // Without the passphrase, say the alias is taken but not where (the "already exists"
+    // message would name the hidden collection)
+    if hidden_taken(&req, &state, &collections, form.shortcut.trim()) {
+        return HttpResponse::Conflict().body(format!(
+            "Shortcut '{}' is taken by a hidden shortcut. Unlock /hidden to change it.",
+            form.shortcut.trim()
+        ));
+    }
+
+    // Pick the requested collection; without one, fall back to the first hidden
+    // collection for the old "Hidden?" checkbox, or the first visible one.
+    let target = match form.collection.as_deref().filter(|c| !c.is_empty()) {
+        Some(name) => collections.iter().position(|c| c.name == name),
+        None => {
+            let is_hidden = form.hidden.is_some();
+            collections.iter().position(|c| c.hidden == is_hidden && !c.directory)
         }
+    };
+    let Some(index) = target else {
+        return HttpResponse::BadRequest().body("Unknown shortcut collection.");
+    };
+
+    let input = match ShortcutInput::from_form(&form.details, &form.url) {
+        Ok(input) => input,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = create_shortcut(&state, &mut collections, index, form.shortcut.trim(), input, &actor(&req)) {
+        return e.into_response();
     }
 
     // Redirect back to the home page
@@ -83,51 +697,213 @@ pub async fn add_shortcut(
 }
 
 
+// Handler for editing a shortcut in place. The alias can be renamed and its URL and
+// details changed; it stays in the collection (file) it came from.
+#[post("/edit_shortcut")]
+pub async fn edit_shortcut(
+    req: HttpRequest,
+    form: Form<EditShortcutForm>,
+    state: Data<Arc<AppState>>,
+) -> impl Responder {
+    let mut collections = state.collections.lock().unwrap();
+    if let Some(response) = guard_hidden(&req, &state, &collections, form.original_key.trim()) {
+        return response;
+    }
+
+    let input = match ShortcutInput::from_form(&form.details, &form.url) {
+        Ok(input) => input,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = update_shortcut(&state, &mut collections, form.original_key.trim(), form.shortcut.trim(), input, &actor(&req)) {
+        return e.into_response();
+    }
+
+    // Redirect back to the home page
+    HttpResponse::Found()
+        .append_header(("Location", "/"))
+        .finish()
+}
+
 // NEW: Handler for deleting a shortcut
 #[post("/delete_shortcut")]
 pub async fn delete_shortcut(
+    req: HttpRequest,
     form: Form<DeleteShortcutForm>,
     state: Data<Arc<AppState>>,
 ) -> impl Responder {
-    let key = form.key.trim();
-    if key.is_empty() {
-        return HttpResponse::BadRequest().body("Shortcut key cannot be empty.");
+    let mut collections = state.collections.lock().unwrap();
+    if let Some(response) = guard_hidden(&req, &state, &collections, form.key.trim()) {
+        return response;
     }
 
-    // Attempt to delete from all three collections and save if successful.
-    // We check `work_shortcuts` and `hidden_shortcuts` before `shortcuts` 
-    // to ensure proper file persistence logic is isolated.
+    // A key that is already gone needs no error; the home page just won't list it
+    match remove_shortcut(&mut collections, form.key.trim(), &actor(&req)) {
+        Ok(()) | Err(ShortcutError::NotFound(_)) => {}
+        Err(e) => return e.into_response(),
+    }
 
-    // 1. Check and delete from work shortcuts
-    {
-        let mut work_shortcuts = state.work_shortcuts.lock().unwrap();
-        if work_shortcuts.remove(key).is_some() {
-            if let Err(e) = save_shortcuts(WORK_SHORTCUTS_FILE, &work_shortcuts) {
-                eprintln!("Failed to save work shortcuts after deletion: {}", e);
-            }
+    // Redirect back to the home page
+    HttpResponse::Found().append_header(("Location", "/")).finish()
+}
+
+// Handler for POST /shortcuts/expired/delete: removes every expired shortcut from the
+// writable, visible collections. Hidden ones are managed from /hidden.
+#[post("/shortcuts/expired/delete")]
+pub async fn delete_expired(req: HttpRequest, state: Data<Arc<AppState>>) -> impl Responder {
+    let mut collections = state.collections.lock().unwrap();
+    let now = current_ts();
+    let who = actor(&req);
+
+    for collection in collections.iter_mut().filter(|c| !c.directory && !c.hidden) {
+        let expired: Vec<String> = collection
+            .shortcuts
+            .iter()
+            .filter(|(_, s)| s.is_expired(now))
+            .map(|(k, _)| k.clone())
+            .collect();
+        if expired.is_empty() {
+            continue;
         }
-    }
-    
-    // 2. Check and delete from hidden shortcuts
-    {
-        let mut hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
-        if hidden_shortcuts.remove(key).is_some() {
-            if let Err(e) = save_shortcuts(HIDDEN_SHORTCUTS_FILE, &hidden_shortcuts) {
-                eprintln!("Failed to save hidden shortcuts after deletion: {}", e);
-            }
+
+        let removed: Vec<(String, Shortcut)> = expired
+            .into_iter()
+            .filter_map(|k| collection.shortcuts.remove(&k).map(|s| (k, s)))
+            .collect();
+        if let Err(e) = save_collection(collection) {
+            collection.shortcuts.extend(removed);
+            return e.into_response();
         }
-    }
-    
-    // 3. Check and delete from visible shortcuts
-    {
-        let mut shortcuts = state.shortcuts.lock().unwrap();
-        if shortcuts.remove(key).is_some() {
-            if let Err(e) = save_shortcuts(SHORTCUTS_FILE, &shortcuts) {
-                eprintln!("Failed to save visible shortcuts after deletion: {}", e);
-            }
+        for (key, old) in &removed {
+            let mut change = Change::new(&who, &collection.name, key, Some(old), None);
+            change.note = "expired cleanup".to_string();
+            history::record(&change);
         }
     }
 
-    // Redirect back to the home page
     HttpResponse::Found().append_header(("Location", "/")).finish()
-}
\ No newline at end of file
+}
+
+// Handler for POST /save_shortcut_settings
+#[post("/save_shortcut_settings")]
+pub async fn save_shortcut_settings(
+    form: Form<ShortcutSettingsForm>,
+    state: Data<Arc<AppState>>,
+) -> impl Responder {
+    let mut settings = state.go_settings.lock().unwrap();
+    // Applied to a copy so a rejected number leaves every setting as it was
+    let mut updated = settings.clone();
+    if let Err(e) = apply_settings_form(&mut updated, &form) {
+        return HttpResponse::BadRequest().body(e);
+    }
+    *settings = updated;
+
+    if let Err(e) = save_go_settings(&files().go_settings, &settings) {
+        eprintln!("Failed to save shortcut settings: {}", e);
+        return HttpResponse::InternalServerError().body("Failed to save shortcut settings.");
+    }
+
+    // Redirect back to settings page
+    HttpResponse::Found()
+        .append_header(("Location", "/settings"))
+        .finish()
+}
+
+// Copies the submitted settings form onto `settings`, or says which number is unusable
+fn apply_settings_form(settings: &mut GoSettings, form: &ShortcutSettingsForm) -> Result<(), String> {
+    let defaults = GoSettings::default();
+    settings.template_fallback_url = form.template_fallback_url.trim().to_string();
+    if let Some(url) = &form.inactive_fallback_url {
+        settings.inactive_fallback_url = url.trim().to_string();
+    }
+    if let Some(interval) = parse_setting(form.health_check_interval_secs.as_deref(), defaults.health_check_interval_secs, "Check every")? {
+        settings.health_check_interval_secs = interval;
+    }
+    if let Some(concurrency) = parse_setting(form.health_check_concurrency.as_deref(), defaults.health_check_concurrency, "Links checked at once")? {
+        settings.health_check_concurrency = concurrency.max(1);
+    }
+    if let Some(delay) = parse_setting(form.health_check_host_delay_ms.as_deref(), defaults.health_check_host_delay_ms, "Delay between requests to one host")? {
+        settings.health_check_host_delay_ms = delay;
+    }
+    if let Some(timeout) = parse_setting(form.health_check_timeout_secs.as_deref(), defaults.health_check_timeout_secs, "Request timeout")? {
+        settings.health_check_timeout_secs = timeout.max(1);
+    }
+    Ok(())
+}
+
+// Reads a number from the settings form. A missing field keeps the current value (None), a
+// blank one means `default`, and spaces around the number are ignored.
+fn parse_setting<T: FromStr>(value: Option<&str>, default: T, label: &str) -> Result<Option<T>, String> {
+    match value.map(str::trim) {
+        None => Ok(None),
+        Some("") => Ok(Some(default)),
+        Some(text) => text
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{}: '{}' is not a whole number.", label, text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn save_refuses_to_overwrite_an_edit_on_disk() {
+        let path = std::env::temp_dir().join(format!("go-service-save-{}.json", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, r#"{"gh": "https://github.com"}"#).unwrap();
+
+        let config = CollectionConfig { name: "Test".to_string(), file: path.clone(), hidden: false, priority: None, directory: false };
+        let mut collection = ShortcutCollection::new(&config, 1, read_collection(&path, false).unwrap().0);
+        collection.modified = file_modified(&path);
+
+        // Our own saves keep the recorded time in step
+        collection.shortcuts.insert("docs".to_string(), Shortcut::new("https://docs.rs"));
+        assert!(save_collection(&mut collection).is_ok());
+        collection.shortcuts.insert("crates".to_string(), Shortcut::new("https://crates.io"));
+        assert!(save_collection(&mut collection).is_ok());
+
+        // Someone else writes the file before the watcher reloads it
+        let edited = fs::File::options().write(true).open(&path).unwrap();
+        edited.set_modified(collection.modified.unwrap() + Duration::from_secs(5)).unwrap();
+        drop(edited);
+        let on_disk = fs::read_to_string(&path).unwrap();
+
+        collection.shortcuts.remove("gh");
+        assert!(matches!(save_collection(&mut collection), Err(ShortcutError::Conflict(_))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), on_disk);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn settings_form_numbers_are_lenient() {
+        let form = |interval: &str, concurrency: &str| ShortcutSettingsForm {
+            template_fallback_url: " https://search.example/?q={0} ".to_string(),
+            inactive_fallback_url: None,
+            health_check_interval_secs: Some(interval.to_string()),
+            health_check_concurrency: Some(concurrency.to_string()),
+            health_check_host_delay_ms: None,
+            health_check_timeout_secs: Some(" 7 ".to_string()),
+        };
+        let defaults = GoSettings::default();
+        let mut settings = GoSettings { health_check_host_delay_ms: 1234, ..GoSettings::default() };
+
+        apply_settings_form(&mut settings, &form(" 60", "0")).unwrap();
+        assert_eq!(settings.template_fallback_url, "https://search.example/?q={0}");
+        assert_eq!(settings.health_check_interval_secs, 60);
+        assert_eq!(settings.health_check_concurrency, 1);
+        assert_eq!(settings.health_check_timeout_secs, 7);
+        // Not submitted, so unchanged
+        assert_eq!(settings.health_check_host_delay_ms, 1234);
+
+        // A cleared field goes back to the default
+        apply_settings_form(&mut settings, &form("", "  ")).unwrap();
+        assert_eq!(settings.health_check_interval_secs, defaults.health_check_interval_secs);
+        assert_eq!(settings.health_check_concurrency, defaults.health_check_concurrency);
+
+        let error = apply_settings_form(&mut settings, &form("soon", "4")).unwrap_err();
+        assert_eq!(error, "Check every: 'soon' is not a whole number.");
+    }
+}
@@ -1,6 +1,6 @@
 use actix_web::{
     get, post,
-    web::{Data, Form}, 
+    web::{Data, Form, Query}, 
     HttpResponse, Responder,
 };
 use serde::Deserialize;
@@ -12,11 +12,9 @@ use std::{
 };
 
 use crate::app_state::{AppState, Theme};
-use crate::base_page::{render_base_page, render_settings_page};
-
-// File constants
-pub static THEMES_FILE: &str = "themes.json";
-pub static CURRENT_THEME_FILE: &str = "current_theme.json";
+use crate::base_page::{render_base_page, render_settings_page, render_shortcut_settings};
+use crate::config::files;
+use crate::rules::render_rules_settings;
 
 // Helper to define a default dark theme
 pub fn default_dark_theme() -> Theme {
@@ -79,13 +77,29 @@ pub struct ThemeForm {
 }
 
 
+// Query parameters accepted by GET /settings
+#[derive(Deserialize)]
+pub struct SettingsQuery {
+    pub rule_test: Option<String>,
+}
+
 // Handler for GET /settings
 #[get("/settings")]
-pub async fn get_settings(state: Data<Arc<AppState>>) -> impl Responder {
+pub async fn get_settings(query: Query<SettingsQuery>, state: Data<Arc<AppState>>) -> impl Responder {
     let current_theme = state.current_theme.lock().unwrap();
     let saved_themes = state.saved_themes.lock().unwrap();
+    let go_settings = state.go_settings.lock().unwrap();
+    let rules = state.rules.lock().unwrap();
+
+    // Path typed into the rule test box, if any
+    let rule_test = query.rule_test.as_deref().map(|p| p.trim().trim_start_matches('/'));
     
-    let content = render_settings_page(&current_theme, &saved_themes);
+    let content = format!(
+        "{}{}{}",
+        render_settings_page(&current_theme, &saved_themes),
+        render_shortcut_settings(&go_settings),
+        render_rules_settings(&rules, rule_test)
+    );
 
     let html_output = render_base_page("Settings - Theme Customization", &content, &current_theme);
 
@@ -108,7 +122,7 @@ pub async fn save_theme(
         if let Some(loaded_theme) = saved_themes.get(&load_name) {
             *current_theme = loaded_theme.clone();
             // Persist the newly loaded theme as the current theme
-            if let Err(e) = save_current_theme(CURRENT_THEME_FILE, &current_theme) {
+            if let Err(e) = save_current_theme(&files().current_theme, &current_theme) {
                 eprintln!("Failed to save current theme after loading: {}", e);
             }
         }
@@ -139,7 +153,7 @@ pub async fn save_theme(
         *current_theme = new_theme.clone();
         
         // Persist the current theme regardless of save action
-        if let Err(e) = save_current_theme(CURRENT_THEME_FILE, &current_theme) {
+        if let Err(e) = save_current_theme(&files().current_theme, &current_theme) {
             eprintln!("Failed to save current theme: {}", e);
             return HttpResponse::InternalServerError().body("Failed to save current theme state.");
         }
@@ -151,7 +165,7 @@ pub async fn save_theme(
         saved_themes.insert(new_theme.name.clone(), new_theme);
 
         // Persist all saved themes
-        if let Err(e) = save_themes(THEMES_FILE, &saved_themes) {
+        if let Err(e) = save_themes(&files().themes, &saved_themes) {
             eprintln!("Failed to save themes list: {}", e);
             return HttpResponse::InternalServerError().body("Failed to save themes list.");
         }
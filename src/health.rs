@@ -0,0 +1,309 @@
+use actix_web::{post, web::Data, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::app_state::{AppState, ShortcutCollection};
+use crate::clock::{current_ts, format_datetime};
+use crate::config::files;
+use crate::http_client;
+use crate::resolve::host_of;
+
+// How often the background job wakes up to see whether a check is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(30);
+
+// Set while a run is in progress, so a manual check can't overlap the scheduled one
+static RUNNING: AtomicBool = AtomicBool::new(false);
+// Unix seconds when the last run finished
+static LAST_RUN: AtomicU64 = AtomicU64::new(0);
+
+// Clears RUNNING when a run ends, including one cut short by a panic, so later runs aren't
+// blocked until a restart
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+// The outcome of checking one URL. Redirects are recorded, not followed.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LinkHealth {
+    // "HEAD", or "GET" when the server refused the HEAD request
+    pub method: String,
+    // None when no response came back (DNS failure, refused connection, timeout)
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub redirect_to: String,
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    pub checked_at: u64,
+}
+
+impl LinkHealth {
+    pub fn is_broken(&self) -> bool {
+        self.status.is_none_or(|s| s >= 400)
+    }
+
+    pub fn is_redirect(&self) -> bool {
+        matches!(self.status, Some(300..=399))
+    }
+
+    // Short description for tables and tooltips, e.g. "404", "301 -> https://..." or the error
+    pub fn summary(&self) -> String {
+        match self.status {
+            None => self.error.clone(),
+            Some(status) if self.is_redirect() && !self.redirect_to.is_empty() => {
+                format!("{} -> {}", status, self.redirect_to)
+            }
+            Some(status) => status.to_string(),
+        }
+    }
+}
+
+pub fn load_health(path: &str) -> io::Result<HashMap<String, LinkHealth>> {
+    let data = fs::read_to_string(path)?;
+    let map: HashMap<String, LinkHealth> = serde_json::from_str(&data)?;
+    Ok(map)
+}
+
+fn save_health(path: &str, health: &HashMap<String, LinkHealth>) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(health)?;
+    fs::write(path, data)
+}
+
+// Starts the background job that re-checks every link once the configured interval has passed.
+pub fn spawn_health_checker(state: Arc<AppState>) {
+    // Carry on from the previous results instead of checking everything at every restart
+    let last = state.health.lock().unwrap().values().map(|h| h.checked_at).max().unwrap_or(0);
+    LAST_RUN.store(last, Ordering::SeqCst);
+
+    thread::spawn(move || loop {
+        let interval = state.go_settings.lock().unwrap().health_check_interval_secs;
+        if interval > 0 && current_ts() >= LAST_RUN.load(Ordering::SeqCst) + interval {
+            run_checks(&state);
+        }
+        thread::sleep(SCHEDULER_TICK);
+    });
+}
+
+// Checks every http(s) shortcut destination once and replaces the stored results.
+// Templates and `go:` chains are skipped since they aren't fetchable as-is.
+pub fn run_checks(state: &AppState) {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let _running = RunningGuard;
+
+    let settings = state.go_settings.lock().unwrap().clone();
+    let urls = checkable_urls(&state.collections.lock().unwrap());
+    let host_delay = Duration::from_millis(settings.health_check_host_delay_ms);
+
+    let queue = Mutex::new(urls);
+    let next_slot: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+    let results = Mutex::new(HashMap::new());
+
+    thread::scope(|scope| {
+        for _ in 0..settings.health_check_concurrency.max(1) {
+            scope.spawn(|| loop {
+                let Some(url) = queue.lock().unwrap().pop() else {
+                    break;
+                };
+                let health = check_url(&url, settings.health_check_timeout_secs, || {
+                    wait_for_host(&next_slot, host_of(&url), host_delay)
+                });
+                results.lock().unwrap().insert(url, health);
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap();
+    let broken = results.values().filter(|h| h.is_broken()).count();
+    eprintln!("Checked {} links, {} broken", results.len(), broken);
+
+    let mut health = state.health.lock().unwrap();
+    *health = results;
+    if let Err(e) = save_health(&files().health, &health) {
+        eprintln!("Failed to save link health: {}", e);
+    }
+
+    LAST_RUN.store(current_ts(), Ordering::SeqCst);
+}
+
+fn checkable_urls(collections: &[ShortcutCollection]) -> Vec<String> {
+    let urls: BTreeSet<&str> = collections
+        .iter()
+        .flat_map(|c| c.shortcuts.values())
+        .map(|s| s.url.as_str())
+        .filter(|u| (u.starts_with("http://") || u.starts_with("https://")) && !u.contains('{'))
+        .collect();
+    urls.into_iter().map(String::from).collect()
+}
+
+// Per-host rate limit: reserves the next free slot for `host` and sleeps until it comes up
+fn wait_for_host(next_slot: &Mutex<HashMap<String, Instant>>, host: &str, delay: Duration) {
+    let wait = {
+        let mut slots = next_slot.lock().unwrap();
+        let now = Instant::now();
+        let slot = slots.get(host).copied().filter(|s| *s > now).unwrap_or(now);
+        slots.insert(host.to_string(), slot + delay);
+        slot - now
+    };
+    thread::sleep(wait);
+}
+
+// HEAD first; some servers don't implement it, so an error status is confirmed with a GET.
+// `before_request` runs ahead of each request (the per-host rate limit).
+fn check_url(url: &str, timeout_secs: u64, before_request: impl Fn()) -> LinkHealth {
+    before_request();
+    let head = request_check(url, "HEAD", timeout_secs);
+    if head.status.is_some_and(|s| s >= 400) {
+        before_request();
+        return request_check(url, "GET", timeout_secs);
+    }
+    head
+}
+
+// One request, reading only the status line and headers
+fn request_check(url: &str, method: &str, timeout_secs: u64) -> LinkHealth {
+    let mut health = LinkHealth {
+        method: method.to_string(),
+        checked_at: current_ts(),
+        ..Default::default()
+    };
+
+    let started = Instant::now();
+    match http_client::status_only(method, url, Duration::from_secs(timeout_secs.max(1))) {
+        Ok(response) => {
+            health.status = Some(response.status);
+            health.latency_ms = started.elapsed().as_millis() as u64;
+            if health.is_redirect() {
+                health.redirect_to = response.header("location").map(|l| http_client::join_url(url, l)).unwrap_or_default();
+            }
+        }
+        Err(e) => health.error = e,
+    }
+    health
+}
+
+// Handler for POST /health/check
+// Starts a run straight away (in the background) instead of waiting for the schedule.
+#[post("/health/check")]
+pub async fn health_check_now(state: Data<Arc<AppState>>) -> impl Responder {
+    let state = state.get_ref().clone();
+    thread::spawn(move || run_checks(&state));
+
+    HttpResponse::Found()
+        .append_header(("Location", "/stats#health"))
+        .finish()
+}
+
+// "Checking now" or when the last run finished, for the /stats page
+pub fn last_run_description() -> String {
+    if RUNNING.load(Ordering::SeqCst) {
+        return "A check is running now; reload the page to see the results.".to_string();
+    }
+    match LAST_RUN.load(Ordering::SeqCst) {
+        0 => "Links have not been checked yet.".to_string(),
+        ts => format!("Last checked {} (UTC).", format_datetime(ts)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    // A tiny HTTP server for the checker: answers by path, one request per connection
+    fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = [0u8; 1024];
+                let n = stream.read(&mut request).unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..n]).to_string();
+                let head = request.starts_with("HEAD ");
+                let path = request.split(' ').nth(1).unwrap_or("/");
+                let status = match path {
+                    "/ok" => "200 OK",
+                    "/moved" => "301 Moved Permanently\r\nLocation: /ok",
+                    "/get-only" if head => "405 Method Not Allowed",
+                    "/get-only" => "200 OK",
+                    _ => "404 Not Found",
+                };
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            }
+        });
+        base
+    }
+
+    #[test]
+    fn records_status_redirect_and_broken_links() {
+        let base = serve();
+
+        let ok = check_url(&format!("{}/ok", base), 5, || {});
+        assert_eq!(ok.status, Some(200));
+        assert_eq!(ok.method, "HEAD");
+        assert!(!ok.is_broken() && !ok.is_redirect());
+
+        let moved = check_url(&format!("{}/moved", base), 5, || {});
+        assert_eq!(moved.status, Some(301));
+        assert!(moved.is_redirect() && !moved.is_broken());
+        assert_eq!(moved.redirect_to, format!("{}/ok", base));
+        assert_eq!(moved.summary(), format!("301 -> {}/ok", base));
+
+        let missing = check_url(&format!("{}/missing", base), 5, || {});
+        assert_eq!(missing.status, Some(404));
+        assert_eq!(missing.method, "GET");
+        assert!(missing.is_broken() && !missing.is_redirect());
+    }
+
+    #[test]
+    fn confirms_a_refused_head_with_get() {
+        let base = serve();
+        let requests = AtomicU64::new(0);
+
+        let health = check_url(&format!("{}/get-only", base), 5, || {
+            requests.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(health.status, Some(200));
+        assert_eq!(health.method, "GET");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn no_response_is_broken() {
+        // Bind and drop, so nothing listens on the port
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+        let health = check_url(&format!("http://{}/", addr), 5, || {});
+        assert_eq!(health.status, None);
+        assert!(health.is_broken());
+        assert!(!health.error.is_empty());
+        assert_eq!(health.summary(), health.error);
+    }
+
+    #[test]
+    fn a_panicking_run_does_not_block_later_runs() {
+        let result = std::panic::catch_unwind(|| {
+            assert!(!RUNNING.swap(true, Ordering::SeqCst));
+            let _running = RunningGuard;
+            panic!("poisoned lock");
+        });
+        assert!(result.is_err());
+        assert!(!RUNNING.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,338 @@
+// The /hidden page: lists, edits and deletes the shortcuts of hidden collections once it has
+// been unlocked with `hidden_passphrase`. The unlock is a session cookie of its own, separate
+// from the login in auth.rs, and shortcuts with the `locked` flag only redirect while it lasts.
+
+use actix_web::{
+    cookie::{time::Duration as CookieDuration, Cookie, SameSite},
+    get,
+    http::StatusCode,
+    post,
+    web::{Data, Form, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use htmlescape::encode_minimal;
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::auth::{constant_time_eq, new_session_id, safe_next, session_valid};
+use crate::base_page::render_base_page;
+use crate::clock::{current_ts, datetime_input_value};
+use crate::config::config;
+use crate::elements::shortcut::{
+    find_shortcut, remove_from_collection, update_shortcut, DeleteShortcutForm, ShortcutDetailsForm, ShortcutInput,
+};
+use crate::history::actor;
+use crate::resolve::encode_component;
+
+pub static UNLOCK_COOKIE: &str = "go_hidden";
+
+#[derive(Deserialize)]
+pub struct HiddenQuery {
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UnlockForm {
+    pub passphrase: String,
+    #[serde(default)]
+    pub next: String,
+}
+
+// Like EditShortcutForm, plus the lock checkbox only this page offers
+#[derive(Deserialize)]
+pub struct HiddenEditForm {
+    pub original_key: String,
+    pub shortcut: String,
+    pub url: String,
+    pub locked: Option<String>,
+    #[serde(flatten)]
+    pub details: ShortcutDetailsForm,
+}
+
+// True while the request's unlock cookie is valid
+pub fn is_unlocked(req: &HttpRequest, state: &AppState) -> bool {
+    req.cookie(UNLOCK_COOKIE).is_some_and(|c| session_valid(&state.unlocked, c.value()))
+}
+
+// Sends the browser to the unlock form, coming back to `next` afterwards
+pub fn unlock_redirect(next: &str) -> HttpResponse {
+    HttpResponse::Found()
+        .append_header(("Location", format!("/hidden?next={}", encode_component(next))))
+        .finish()
+}
+
+// The collection holding `key` if it is a hidden one
+fn hidden_entry<'a>(collections: &'a [ShortcutCollection], key: &str) -> Option<&'a ShortcutCollection> {
+    find_shortcut(collections, key).map(|(c, _)| c).filter(|c| c.hidden)
+}
+
+fn locked_response() -> HttpResponse {
+    HttpResponse::Forbidden().body("Unlock the hidden shortcuts on /hidden first.")
+}
+
+fn render_unlock(state: &AppState, next: &str, error: Option<&str>, status: StatusCode) -> HttpResponse {
+    let current_theme = state.current_theme.lock().unwrap();
+
+    let form = if config().hidden_passphrase.is_empty() {
+        "<p>No passphrase is set, so hidden shortcuts stay locked. Set <code>hidden_passphrase</code> in the config file (or GO_SERVICE_HIDDEN_PASSPHRASE) to use this page.</p>".to_string()
+    } else {
+        format!(
+            r#"
+        <form action="/hidden/unlock" method="POST">
+            <input type="hidden" name="next" value="{}">
+            <div class="settings-grid">
+                <div>
+                    <label for="passphrase">Passphrase:</label>
+                    <input type="password" id="passphrase" name="passphrase" autofocus required>
+                </div>
+            </div>
+            <button type="submit" class="form-submit-btn">Unlock</button>
+        </form>"#,
+            encode_minimal(next)
+        )
+    };
+    let error = error
+        .map(|e| format!("<p class=\"login-error\">{}</p>", encode_minimal(e)))
+        .unwrap_or_default();
+
+    let content = format!(
+        r#"
+    <div class="settings-form">
+        <h1>Hidden Shortcuts</h1>
+        <p>Hidden shortcuts and locked redirects need the passphrase.</p>
+        {}
+        {}
+    </div>
+    "#,
+        error, form
+    );
+
+    HttpResponse::build(status)
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Unlock Hidden Shortcuts", &content, &current_theme))
+}
+
+// One hidden shortcut: a summary line that opens its edit and delete forms
+fn render_hidden_entry(collection: &ShortcutCollection, key: &str, shortcut: &Shortcut) -> String {
+    let summary = format!(
+        "<a href=\"/{0}\">{0}</a> &rarr; {1}{2}{3}",
+        encode_minimal(key),
+        encode_minimal(&shortcut.url),
+        if shortcut.locked { " <span class=\"tag\">locked</span>" } else { "" },
+        if shortcut.description.is_empty() {
+            String::new()
+        } else {
+            format!(" <small>{}</small>", encode_minimal(&shortcut.description))
+        }
+    );
+
+    if collection.directory {
+        let source = collection.sources.get(key).unwrap_or(&collection.file);
+        return format!(
+            "<li>{} <small class=\"shortcut-source\">{}</small></li>",
+            summary,
+            encode_minimal(source)
+        );
+    }
+
+    format!(
+        r#"
+        <li>
+        <details class="hidden-shortcut">
+            <summary>{summary}</summary>
+            <form action="/hidden/edit" method="POST">
+                <input type="hidden" name="original_key" value="{key}">
+                <div class="settings-grid">
+                    <div><label>Shortcut:</label><input type="text" name="shortcut" value="{key}" required></div>
+                    <div><label>URL:</label><input type="text" name="url" value="{url}" required></div>
+                    <div><label>Description:</label><input type="text" name="description" value="{description}"></div>
+                    <div><label>Tags (comma separated):</label><input type="text" name="tags" value="{tags}"></div>
+                    <div><label>Owner:</label><input type="text" name="owner" value="{owner}"></div>
+                    <div><label>Notes:</label><input type="text" name="notes" value="{notes}"></div>
+                    <div><label>Active from (UTC):</label><input type="datetime-local" name="active_from" value="{active_from}"></div>
+                    <div><label>Expires (UTC):</label><input type="datetime-local" name="expires_at" value="{expires_at}"></div>
+                    <div><label><input type="checkbox" name="locked" value="1"{checked}> Redirect only while unlocked</label></div>
+                </div>
+                <div class="theme-action-buttons">
+                    <button type="submit" class="form-submit-btn">Save</button>
+                </div>
+            </form>
+            <form action="/hidden/delete" method="POST" onsubmit="return confirm('Delete shortcut {key}?');">
+                <input type="hidden" name="key" value="{key}">
+                <div class="theme-action-buttons">
+                    <button type="submit" class="delete-button">Delete</button>
+                </div>
+            </form>
+        </details>
+        </li>"#,
+        summary = summary,
+        key = encode_minimal(key),
+        url = encode_minimal(&shortcut.url),
+        description = encode_minimal(&shortcut.description),
+        tags = encode_minimal(&shortcut.tags.join(", ")),
+        owner = encode_minimal(&shortcut.owner),
+        notes = encode_minimal(&shortcut.notes),
+        checked = if shortcut.locked { " checked" } else { "" },
+        active_from = datetime_input_value(shortcut.active_from),
+        expires_at = datetime_input_value(shortcut.expires_at),
+    )
+}
+
+fn render_hidden_list(state: &AppState) -> HttpResponse {
+    let collections = state.collections.lock().unwrap();
+    let current_theme = state.current_theme.lock().unwrap();
+
+    let sections: String = collections
+        .iter()
+        .filter(|c| c.hidden)
+        .map(|c| {
+            let mut keys: Vec<&String> = c.shortcuts.keys().collect();
+            keys.sort();
+            let items: String = keys.iter().map(|k| render_hidden_entry(c, k, &c.shortcuts[*k])).collect();
+            format!(
+                r#"
+        <h2 class="collection-heading">{} <small>({}, {} shortcut(s){})</small></h2>
+        <ul class="hidden-shortcuts">{}</ul>"#,
+                encode_minimal(&c.name),
+                encode_minimal(&c.file),
+                c.shortcuts.len(),
+                if c.directory { ", read-only" } else { "" },
+                if items.is_empty() { "<li>No shortcuts yet.</li>".to_string() } else { items }
+            )
+        })
+        .collect();
+    let sections = if sections.is_empty() {
+        "<p>There are no hidden collections. Mark one as <code>\"hidden\": true</code> in collections.json.</p>".to_string()
+    } else {
+        sections
+    };
+
+    let content = format!(
+        r#"
+    <div class="settings-form">
+        <h1>Hidden Shortcuts</h1>
+        <p>Unlocked for {} minutes. Add hidden shortcuts from the home page by picking a hidden collection.</p>
+        <form action="/hidden/lock" method="POST">
+            <button type="submit" class="form-submit-btn">Lock Now</button>
+        </form>
+        {}
+    </div>
+    "#,
+        config().hidden_unlock_minutes.max(1),
+        sections
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Hidden Shortcuts", &content, &current_theme))
+}
+
+// Handler for GET /hidden
+#[get("/hidden")]
+pub async fn hidden_get(req: HttpRequest, query: Query<HiddenQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    if !is_unlocked(&req, &state) {
+        let next = query.next.as_deref().map(safe_next).unwrap_or("/hidden");
+        return render_unlock(&state, next, None, StatusCode::OK);
+    }
+    render_hidden_list(&state)
+}
+
+// Handler for POST /hidden/unlock
+#[post("/hidden/unlock")]
+pub async fn hidden_unlock(req: HttpRequest, form: Form<UnlockForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let settings = config();
+    let next = if form.next.is_empty() { "/hidden" } else { safe_next(&form.next) };
+
+    if settings.hidden_passphrase.is_empty() || !constant_time_eq(&form.passphrase, &settings.hidden_passphrase) {
+        // Slow down passphrase guessing
+        actix_web::rt::time::sleep(Duration::from_secs(1)).await;
+        return render_unlock(&state, next, Some("Wrong passphrase."), StatusCode::UNAUTHORIZED);
+    }
+
+    let lifetime = settings.hidden_unlock_minutes.max(1) * 60;
+    let id = new_session_id();
+    state.unlocked.lock().unwrap().insert(id.clone(), current_ts() + lifetime);
+
+    let cookie = Cookie::build(UNLOCK_COOKIE, id)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .secure(req.connection_info().scheme() == "https")
+        .max_age(CookieDuration::seconds(lifetime as i64))
+        .finish();
+
+    HttpResponse::Found()
+        .cookie(cookie)
+        .append_header(("Location", next))
+        .finish()
+}
+
+// Handler for POST /hidden/lock
+#[post("/hidden/lock")]
+pub async fn hidden_lock(req: HttpRequest, state: Data<Arc<AppState>>) -> impl Responder {
+    if let Some(cookie) = req.cookie(UNLOCK_COOKIE) {
+        state.unlocked.lock().unwrap().remove(cookie.value());
+    }
+
+    let mut removal = Cookie::build(UNLOCK_COOKIE, "").path("/").finish();
+    removal.make_removal();
+
+    HttpResponse::Found()
+        .cookie(removal)
+        .append_header(("Location", "/"))
+        .finish()
+}
+
+// Handler for POST /hidden/edit
+#[post("/hidden/edit")]
+pub async fn hidden_edit(req: HttpRequest, form: Form<HiddenEditForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    if !is_unlocked(&req, &state) {
+        return locked_response();
+    }
+    let mut collections = state.collections.lock().unwrap();
+    let original_key = form.original_key.trim();
+    if hidden_entry(&collections, original_key).is_none() {
+        return HttpResponse::NotFound().body(format!("Hidden shortcut '{}' does not exist.", original_key));
+    }
+
+    let mut input = match ShortcutInput::from_form(&form.details, &form.url) {
+        Ok(input) => input,
+        Err(e) => return e.into_response(),
+    };
+    input.locked = Some(form.locked.is_some());
+    if let Err(e) = update_shortcut(&state, &mut collections, original_key, form.shortcut.trim(), input, &actor(&req)) {
+        return e.into_response();
+    }
+
+    HttpResponse::Found().append_header(("Location", "/hidden")).finish()
+}
+
+// Handler for POST /hidden/delete
+#[post("/hidden/delete")]
+pub async fn hidden_delete(req: HttpRequest, form: Form<DeleteShortcutForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    if !is_unlocked(&req, &state) {
+        return locked_response();
+    }
+    let mut collections = state.collections.lock().unwrap();
+    let key = form.key.trim();
+    // Only the hidden entry; a visible shortcut with the same alias stays
+    let Some(index) = collections.iter().position(|c| c.hidden && c.shortcuts.contains_key(key)) else {
+        return HttpResponse::NotFound().body(format!("Hidden shortcut '{}' does not exist.", key));
+    };
+    if let Err(e) = remove_from_collection(&mut collections[index], key, &actor(&req)) {
+        return e.into_response();
+    }
+
+    HttpResponse::Found().append_header(("Location", "/hidden")).finish()
+}
+
+// The home page forms can't be used to change hidden shortcuts while locked
+pub fn guard_hidden(req: &HttpRequest, state: &AppState, collections: &[ShortcutCollection], key: &str) -> Option<HttpResponse> {
+    hidden_taken(req, state, collections, key).then(locked_response)
+}
+
+// True when `key` belongs to a hidden collection and /hidden is locked for this request
+pub fn hidden_taken(req: &HttpRequest, state: &AppState, collections: &[ShortcutCollection], key: &str) -> bool {
+    collections.iter().any(|c| c.hidden && c.shortcuts.contains_key(key)) && !is_unlocked(req, state)
+}
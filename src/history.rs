@@ -0,0 +1,581 @@
+// Append-only log of every change to the shortcut files, and the /shortcuts/history page that
+// reverts a single change or rolls a collection back to an earlier point.
+//
+// Each line of shortcut-history.jsonl is one Change; its line number is the change id. Edits
+// made to the files by hand (picked up by the watcher) aren't logged, so a restore only undoes
+// logged changes.
+
+use actix_web::{
+    get, post,
+    web::{Data, Form, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use htmlescape::encode_minimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::auth;
+use crate::base_page::render_base_page;
+use crate::clock::{current_ts, format_datetime, parse_datetime};
+use crate::config::files;
+use crate::elements::shortcut::{check_chain, save_collection, ShortcutError};
+use crate::hidden;
+use crate::resolve::encode_component;
+
+// How many changes the page shows, newest first
+const PAGE_LIMIT: usize = 300;
+
+// One change to one alias. `old` is None for an added alias, `new` None for a deleted one.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Change {
+    pub at: u64,
+    pub who: String,
+    pub collection: String,
+    pub key: String,
+    // Set when the change renamed `renamed_from` to `key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    pub old: Option<Shortcut>,
+    pub new: Option<Shortcut>,
+    // Why the change was made when it wasn't a plain edit (import, revert, restore)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+impl Change {
+    pub fn new(who: &str, collection: &str, key: &str, old: Option<&Shortcut>, new: Option<&Shortcut>) -> Self {
+        Change {
+            at: current_ts(),
+            who: who.to_string(),
+            collection: collection.to_string(),
+            key: key.to_string(),
+            renamed_from: None,
+            old: old.cloned(),
+            new: new.cloned(),
+            note: String::new(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (&self.old, &self.new, &self.renamed_from) {
+            (None, _, _) => "Added".to_string(),
+            (_, None, _) => "Deleted".to_string(),
+            (_, _, Some(from)) => format!("Renamed from {}", encode_minimal(from)),
+            _ => "Changed".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    pub collection: Option<String>,
+    pub key: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RevertForm {
+    pub id: usize,
+}
+
+// Restores `collection` to just after change `after`, or to how it was at the time `at`
+#[derive(Deserialize)]
+pub struct RestoreForm {
+    pub collection: String,
+    pub after: Option<usize>,
+    pub at: Option<String>,
+}
+
+// Who made a change: the client address, and whether it came through the API
+pub fn actor(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    let address = info.realip_remote_addr().unwrap_or("unknown");
+    let address = address
+        .parse::<SocketAddr>()
+        .map(|a| a.ip().to_string())
+        .unwrap_or_else(|_| address.to_string());
+    if auth::has_valid_token(req) {
+        format!("{} (API token)", address)
+    } else if req.path().starts_with("/api/") {
+        format!("{} (API)", address)
+    } else {
+        address
+    }
+}
+
+// Who made a change from the command line
+pub fn cli_actor() -> String {
+    format!("cli ({})", env::var("USER").unwrap_or_else(|_| "unknown".to_string()))
+}
+
+// Appends a change to the log. A failure is only logged; the change itself already happened.
+pub fn record(change: &Change) {
+    let result = serde_json::to_string(change).map_err(|e| e.to_string()).and_then(|line| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&files().history)
+            .and_then(|mut f| writeln!(f, "{}", line))
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to append to {}: {}", files().history, e);
+    }
+}
+
+// Every logged change with its id (line number, starting at 1), oldest first
+pub fn load_changes() -> Vec<(usize, Change)> {
+    parse_changes(&fs::read_to_string(&files().history).unwrap_or_default())
+}
+
+// A line that isn't a change (e.g. damaged by a hand edit) is skipped but still counts, so the
+// ids of the changes after it stay the same
+fn parse_changes(data: &str) -> Vec<(usize, Change)> {
+    data.lines()
+        .enumerate()
+        .filter_map(|(i, line)| serde_json::from_str(line).ok().map(|c| (i + 1, c)))
+        .collect()
+}
+
+// How far back a restore goes: to just after a change, or to a point in time
+enum RestorePoint {
+    After(usize),
+    At(u64),
+}
+
+impl RestorePoint {
+    // True if the change came later, so the restore undoes it
+    fn undoes(&self, id: usize, change: &Change) -> bool {
+        match self {
+            RestorePoint::After(after) => id > *after,
+            RestorePoint::At(ts) => change.at > *ts,
+        }
+    }
+}
+
+// Sets (Some) or removes (None) aliases of one collection in a single save, logging each one.
+// Nothing changes if an alias would clash with another collection or break a `go:` chain.
+fn apply(
+    state: &AppState,
+    collections: &mut [ShortcutCollection],
+    index: usize,
+    updates: Vec<(String, Option<Shortcut>)>,
+    who: &str,
+    note: &str,
+) -> Result<usize, ShortcutError> {
+    if collections[index].directory {
+        return Err(ShortcutError::ReadOnly(format!("{} is read-only.", collections[index].name)));
+    }
+    for (key, value) in &updates {
+        if value.is_some()
+            && let Some(other) = collections.iter().enumerate().find(|(i, c)| *i != index && c.shortcuts.contains_key(key))
+        {
+            return Err(ShortcutError::Conflict(format!(
+                "Shortcut '{}' now exists in {}. Delete it there first.",
+                key, other.1.name
+            )));
+        }
+    }
+
+    let snapshot = collections[index].shortcuts.clone();
+    for (key, value) in &updates {
+        match value {
+            Some(shortcut) => collections[index].shortcuts.insert(key.clone(), shortcut.clone()),
+            None => collections[index].shortcuts.remove(key),
+        };
+    }
+    for (key, _) in updates.iter().filter(|(_, v)| v.is_some()) {
+        if let Err(e) = check_chain(state, collections, key) {
+            collections[index].shortcuts = snapshot;
+            return Err(ShortcutError::Invalid { field: "url", message: e });
+        }
+    }
+    if let Err(e) = save_collection(&mut collections[index]) {
+        collections[index].shortcuts = snapshot;
+        return Err(e);
+    }
+
+    for (key, value) in &updates {
+        let mut change = Change::new(who, &collections[index].name, key, snapshot.get(key), value.as_ref());
+        change.note = note.to_string();
+        record(&change);
+    }
+    Ok(updates.len())
+}
+
+// Undoes one change, provided the alias still looks the way that change left it
+fn revert(state: &AppState, collections: &mut [ShortcutCollection], id: usize, who: &str) -> Result<(), ShortcutError> {
+    let Some((_, change)) = load_changes().into_iter().find(|(i, _)| *i == id) else {
+        return Err(ShortcutError::NotFound(format!("Change #{} does not exist.", id)));
+    };
+    let Some(index) = collections.iter().position(|c| c.name == change.collection) else {
+        return Err(ShortcutError::NotFound(format!("Collection {} no longer exists.", change.collection)));
+    };
+
+    if collections[index].shortcuts.get(&change.key) != change.new.as_ref() {
+        return Err(ShortcutError::Conflict(format!(
+            "Shortcut '{}' has changed since change #{}. Revert the later changes first.",
+            change.key, id
+        )));
+    }
+    let restored_key = change.renamed_from.clone().unwrap_or_else(|| change.key.clone());
+    if change.old.is_some() && restored_key != change.key && collections[index].shortcuts.contains_key(&restored_key) {
+        return Err(ShortcutError::Conflict(format!("Shortcut '{}' exists again.", restored_key)));
+    }
+
+    let mut updates = Vec::new();
+    if restored_key != change.key || change.old.is_none() {
+        updates.push((change.key.clone(), None));
+    }
+    if let Some(old) = change.old {
+        updates.push((restored_key, Some(old)));
+    }
+    apply(state, collections, index, updates, who, &format!("revert of #{}", id)).map(|_| ())
+}
+
+// Rolls a collection back to `point` by undoing every later logged change to it, newest
+// first. Returns how many aliases changed.
+fn restore(
+    state: &AppState,
+    collections: &mut [ShortcutCollection],
+    name: &str,
+    point: RestorePoint,
+    who: &str,
+    note: &str,
+) -> Result<usize, ShortcutError> {
+    let Some(index) = collections.iter().position(|c| c.name == name) else {
+        return Err(ShortcutError::NotFound(format!("Unknown shortcut collection '{}'.", name)));
+    };
+
+    let mut target = collections[index].shortcuts.clone();
+    for (_, change) in load_changes().into_iter().rev().filter(|(id, c)| c.collection == name && point.undoes(*id, c)) {
+        if change.new.is_some() {
+            target.remove(&change.key);
+        }
+        if let Some(old) = change.old {
+            target.insert(change.renamed_from.unwrap_or(change.key), old);
+        }
+    }
+
+    let current = &collections[index].shortcuts;
+    let keys: BTreeSet<&String> = current.keys().chain(target.keys()).collect();
+    let updates: Vec<(String, Option<Shortcut>)> = keys
+        .into_iter()
+        .filter(|k| current.get(*k) != target.get(*k))
+        .map(|k| (k.clone(), target.get(k).cloned()))
+        .collect();
+    if updates.is_empty() {
+        return Ok(0);
+    }
+    apply(state, collections, index, updates, who, note)
+}
+
+fn shortcut_cell(shortcut: Option<&Shortcut>) -> String {
+    match shortcut {
+        None => String::new(),
+        Some(s) if s.description.is_empty() => format!("<span class=\"url\">{}</span>", encode_minimal(&s.url)),
+        Some(s) => format!(
+            "<span class=\"url\">{}</span><div class=\"shortcut-notes\">{}</div>",
+            encode_minimal(&s.url),
+            encode_minimal(&s.description)
+        ),
+    }
+}
+
+// Handler for GET /shortcuts/history?collection=Work&key=gh
+#[get("/shortcuts/history")]
+pub async fn history_get(req: HttpRequest, query: Query<HistoryQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+    let current_theme = state.current_theme.lock().unwrap();
+
+    // Changes to hidden collections only show up once /hidden is unlocked
+    let unlocked = hidden::is_unlocked(&req, &state);
+    let hidden: HashMap<&str, bool> = collections.iter().map(|c| (c.name.as_str(), c.hidden)).collect();
+    let visible = |c: &Change| unlocked || !hidden.get(c.collection.as_str()).copied().unwrap_or(false);
+
+    let collection_filter = query.collection.as_deref().filter(|c| !c.is_empty());
+    let key_filter = query.key.as_deref().filter(|k| !k.is_empty());
+    let changes = load_changes();
+    let hidden_count = changes.iter().filter(|(_, c)| !visible(c)).count();
+
+    let rows: String = changes
+        .iter()
+        .rev()
+        .filter(|(_, c)| visible(c))
+        .filter(|(_, c)| collection_filter.is_none_or(|name| c.collection == name))
+        .filter(|(_, c)| key_filter.is_none_or(|key| c.key == key || c.renamed_from.as_deref() == Some(key)))
+        .take(PAGE_LIMIT)
+        .map(|(id, c)| {
+            format!(
+                r#"<tr><td>#{id}</td><td>{when}</td><td>{who}</td><td><a href="/shortcuts/history?collection={collection_link}">{collection}</a></td><td><a href="/shortcuts/history?key={key_link}">{key}</a></td><td>{change}{note}</td><td>{old}</td><td>{new}</td>
+                <td>
+                    <form action="/shortcuts/history/revert" method="POST" class="history-action" onsubmit="return confirm('Revert change #{id}?');">
+                        <input type="hidden" name="id" value="{id}">
+                        <button type="submit">Revert</button>
+                    </form>
+                    <form action="/shortcuts/history/restore" method="POST" class="history-action" onsubmit="return confirm('Undo every later change to {collection}?');">
+                        <input type="hidden" name="collection" value="{collection}">
+                        <input type="hidden" name="after" value="{id}">
+                        <button type="submit" title="Undo every later change to this collection">Restore to here</button>
+                    </form>
+                </td></tr>"#,
+                id = id,
+                when = format_datetime(c.at),
+                who = encode_minimal(&c.who),
+                collection_link = encode_component(&c.collection),
+                collection = encode_minimal(&c.collection),
+                key_link = encode_component(&c.key),
+                key = encode_minimal(&c.key),
+                change = c.describe(),
+                note = if c.note.is_empty() {
+                    String::new()
+                } else {
+                    format!(" <small>({})</small>", encode_minimal(&c.note))
+                },
+                old = shortcut_cell(c.old.as_ref()),
+                new = shortcut_cell(c.new.as_ref()),
+            )
+        })
+        .collect();
+    let rows = if rows.is_empty() {
+        "<tr><td colspan=\"9\">No changes recorded yet.</td></tr>".to_string()
+    } else {
+        rows
+    };
+
+    let collection_options: String = collections
+        .iter()
+        .filter(|c| !c.directory && (unlocked || !c.hidden))
+        .map(|c| format!("<option value=\"{0}\">{0}</option>", encode_minimal(&c.name)))
+        .collect();
+    let filter_note = match (collection_filter, key_filter) {
+        (None, None) => String::new(),
+        _ => " Filtered: <a href=\"/shortcuts/history\">show all</a>.".to_string(),
+    };
+    let hidden_note = if hidden_count > 0 {
+        format!(" {} change(s) to hidden collections are left out; <a href=\"/hidden?next=/shortcuts/history\">unlock</a> to see them.", hidden_count)
+    } else {
+        String::new()
+    };
+
+    let content = format!(
+        r#"
+    <h1>Shortcut History</h1>
+    <p style="margin: 10px;">Every change made through the pages, the API and the CLI, newest first (times in UTC). Revert undoes one change; Restore to here undoes every later change to that collection.{filter_note}{hidden_note}</p>
+    <form action="/shortcuts/history/restore" method="POST" class="history-restore" onsubmit="return confirm('Restore this collection to the chosen time?');">
+        <label for="restore_collection">Restore</label>
+        <select id="restore_collection" name="collection">{collection_options}</select>
+        <label for="restore_at">to how it was at (UTC)</label>
+        <input type="datetime-local" id="restore_at" name="at" required>
+        <button type="submit">Restore</button>
+    </form>
+    <table class="grid">
+        <thead><tr><th>#</th><th>When</th><th>Who</th><th>Collection</th><th>Shortcut</th><th>Change</th><th>Before</th><th>After</th><th></th></tr></thead>
+        <tbody>{rows}</tbody>
+    </table>
+    "#,
+        filter_note = filter_note,
+        hidden_note = hidden_note,
+        collection_options = collection_options,
+        rows = rows,
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Shortcut History", &content, &current_theme))
+}
+
+// True if `collection` is hidden and /hidden hasn't been unlocked
+fn hidden_locked(req: &HttpRequest, state: &AppState, collections: &[ShortcutCollection], collection: &str) -> bool {
+    collections.iter().any(|c| c.name == collection && c.hidden) && !hidden::is_unlocked(req, state)
+}
+
+// Handler for POST /shortcuts/history/revert
+#[post("/shortcuts/history/revert")]
+pub async fn history_revert(req: HttpRequest, form: Form<RevertForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let mut collections = state.collections.lock().unwrap();
+    let collection = load_changes().into_iter().find(|(i, _)| *i == form.id).map(|(_, c)| c.collection);
+    if collection.is_some_and(|c| hidden_locked(&req, &state, &collections, &c)) {
+        return HttpResponse::Forbidden().body("Unlock the hidden shortcuts on /hidden first.");
+    }
+
+    if let Err(e) = revert(&state, &mut collections, form.id, &actor(&req)) {
+        return e.into_response();
+    }
+    HttpResponse::Found().append_header(("Location", "/shortcuts/history")).finish()
+}
+
+// Handler for POST /shortcuts/history/restore
+#[post("/shortcuts/history/restore")]
+pub async fn history_restore(req: HttpRequest, form: Form<RestoreForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let mut collections = state.collections.lock().unwrap();
+    if hidden_locked(&req, &state, &collections, &form.collection) {
+        return HttpResponse::Forbidden().body("Unlock the hidden shortcuts on /hidden first.");
+    }
+
+    let (point, note) = match (form.after, form.at.as_deref()) {
+        (Some(after), _) => (RestorePoint::After(after), format!("restore to #{}", after)),
+        (None, Some(at)) => {
+            let Some(ts) = parse_datetime(at) else {
+                return HttpResponse::BadRequest().body("Invalid date; use YYYY-MM-DD HH:MM.");
+            };
+            (RestorePoint::At(ts), format!("restore to {} UTC", format_datetime(ts)))
+        }
+        (None, None) => return HttpResponse::BadRequest().body("Choose a change or a time to restore to."),
+    };
+
+    if let Err(e) = restore(&state, &mut collections, &form.collection, point, &actor(&req), &note) {
+        return e.into_response();
+    }
+    HttpResponse::Found()
+        .append_header(("Location", format!("/shortcuts/history?collection={}", encode_component(&form.collection))))
+        .finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::CollectionConfig;
+    use crate::config;
+    use crate::elements::shortcut::{create_shortcut, load_collections, remove_shortcut, save_shortcuts, update_shortcut, ShortcutInput};
+    use std::collections::BTreeMap;
+
+    // State whose only collection is `name`, in a new file in the scratch directory. The log is
+    // shared by every test, so each test uses its own collection name.
+    fn state_with(name: &str, shortcuts: &[(&str, &str)]) -> AppState {
+        config::ensure_data_dir().unwrap();
+        let file = config::data_path(&format!("{}.json", name));
+        let map: HashMap<String, Shortcut> = shortcuts.iter().map(|(k, url)| (k.to_string(), Shortcut::new(url))).collect();
+        save_shortcuts(&file, &map).unwrap();
+        let state = crate::load_app_state();
+        let collection = CollectionConfig { name: name.to_string(), file, hidden: false, priority: None, directory: false };
+        *state.collections.lock().unwrap() = load_collections(&[collection]);
+        state
+    }
+
+    fn urls(collections: &[ShortcutCollection]) -> BTreeMap<&str, &str> {
+        collections[0].shortcuts.iter().map(|(k, s)| (k.as_str(), s.url.as_str())).collect()
+    }
+
+    // Ids of the logged changes to `name`, oldest first
+    fn ids(name: &str) -> Vec<usize> {
+        load_changes().into_iter().filter(|(_, c)| c.collection == name).map(|(id, _)| id).collect()
+    }
+
+    fn input(url: &str) -> ShortcutInput {
+        ShortcutInput::new(url, "", &[], "", "")
+    }
+
+    fn ok<T>(result: Result<T, ShortcutError>) -> T {
+        result.unwrap_or_else(|e| panic!("{}", e.message()))
+    }
+
+    #[test]
+    fn reverts_single_changes() {
+        let state = state_with("history-revert", &[]);
+        let mut collections = state.collections.lock().unwrap();
+        ok(create_shortcut(&state, &mut collections, 0, "gh", input("https://github.com"), "test"));
+        ok(update_shortcut(&state, &mut collections, "gh", "gh", input("https://github.com/rust-lang"), "test"));
+        ok(create_shortcut(&state, &mut collections, 0, "docs", input("https://docs.rs"), "test"));
+        ok(update_shortcut(&state, &mut collections, "docs", "rs", input("https://docs.rs"), "test"));
+        ok(remove_shortcut(&mut collections, "gh", "test"));
+        let [add_gh, edit_gh, add_docs, rename, delete_gh] = ids("history-revert")[..] else {
+            panic!("expected five logged changes");
+        };
+        assert_eq!(urls(&collections), BTreeMap::from([("rs", "https://docs.rs")]));
+
+        // A rename comes back under the old key, and the new key goes
+        ok(revert(&state, &mut collections, rename, "test"));
+        assert_eq!(urls(&collections), BTreeMap::from([("docs", "https://docs.rs")]));
+
+        // An edit to an alias that was deleted since can't be undone on its own
+        assert!(matches!(revert(&state, &mut collections, edit_gh, "test"), Err(ShortcutError::Conflict(_))));
+        ok(revert(&state, &mut collections, delete_gh, "test"));
+        assert_eq!(urls(&collections)["gh"], "https://github.com/rust-lang");
+        ok(revert(&state, &mut collections, edit_gh, "test"));
+        assert_eq!(urls(&collections)["gh"], "https://github.com");
+        ok(revert(&state, &mut collections, add_docs, "test"));
+        assert_eq!(urls(&collections), BTreeMap::from([("gh", "https://github.com")]));
+        ok(revert(&state, &mut collections, add_gh, "test"));
+        assert!(urls(&collections).is_empty());
+
+        // Reverts are logged like any change, and can be reverted in turn
+        let changes = load_changes();
+        let (last, change) = changes.iter().rfind(|(_, c)| c.collection == "history-revert").unwrap();
+        assert_eq!(change.note, format!("revert of #{}", add_gh));
+        ok(revert(&state, &mut collections, *last, "test"));
+        assert_eq!(urls(&collections), BTreeMap::from([("gh", "https://github.com")]));
+
+        assert!(matches!(revert(&state, &mut collections, usize::MAX, "test"), Err(ShortcutError::NotFound(_))));
+        // What's saved is what's in memory
+        let saved = crate::elements::shortcut::load_shortcuts(&collections[0].file).unwrap();
+        let saved: BTreeMap<&str, &str> = saved.iter().map(|(k, s)| (k.as_str(), s.url.as_str())).collect();
+        assert_eq!(saved, urls(&collections));
+    }
+
+    #[test]
+    fn restores_a_collection_to_just_after_a_change() {
+        let state = state_with("history-restore", &[]);
+        let mut collections = state.collections.lock().unwrap();
+        ok(create_shortcut(&state, &mut collections, 0, "a", input("https://a.example/1"), "test"));
+        ok(create_shortcut(&state, &mut collections, 0, "b", input("https://b.example/"), "test"));
+        ok(update_shortcut(&state, &mut collections, "a", "a", input("https://a.example/2"), "test"));
+        ok(update_shortcut(&state, &mut collections, "b", "c", input("https://b.example/"), "test"));
+        ok(remove_shortcut(&mut collections, "a", "test"));
+        let add_b = ids("history-restore")[1];
+
+        let changed = ok(restore(&state, &mut collections, "history-restore", RestorePoint::After(add_b), "test", "restore"));
+        assert_eq!(changed, 3);
+        assert_eq!(urls(&collections), BTreeMap::from([("a", "https://a.example/1"), ("b", "https://b.example/")]));
+
+        // Back to before the first change, which also undoes the restore itself
+        ok(restore(&state, &mut collections, "history-restore", RestorePoint::After(0), "test", "restore"));
+        assert!(urls(&collections).is_empty());
+        assert_eq!(ok(restore(&state, &mut collections, "history-restore", RestorePoint::After(0), "test", "restore")), 0);
+    }
+
+    #[test]
+    fn restores_a_collection_to_a_point_in_time() {
+        // A log written earlier: a added, b added, a edited, b renamed to c
+        let name = "history-restore-time";
+        let state = state_with(name, &[("a", "https://a.example/2"), ("c", "https://b.example/")]);
+        let (a1, a2, b) = (Shortcut::new("https://a.example/1"), Shortcut::new("https://a.example/2"), Shortcut::new("https://b.example/"));
+        let logged = [
+            (1000, "a", None, None, Some(&a1)),
+            (2000, "b", None, None, Some(&b)),
+            (3000, "a", None, Some(&a1), Some(&a2)),
+            (4000, "c", Some("b"), Some(&b), Some(&b)),
+        ];
+        for (at, key, renamed_from, old, new) in logged {
+            let mut change = Change::new("test", name, key, old, new);
+            change.at = at;
+            change.renamed_from = renamed_from.map(String::from);
+            record(&change);
+        }
+        let mut collections = state.collections.lock().unwrap();
+
+        ok(restore(&state, &mut collections, name, RestorePoint::At(2500), "test", "restore"));
+        assert_eq!(urls(&collections), BTreeMap::from([("a", "https://a.example/1"), ("b", "https://b.example/")]));
+        // Nothing happened after now
+        assert_eq!(ok(restore(&state, &mut collections, name, RestorePoint::At(current_ts()), "test", "restore")), 0);
+        ok(restore(&state, &mut collections, name, RestorePoint::At(500), "test", "restore"));
+        assert!(urls(&collections).is_empty());
+    }
+
+    #[test]
+    fn ids_are_line_numbers() {
+        let change = serde_json::to_string(&Change::new("test", "Work", "gh", None, Some(&Shortcut::new("https://github.com")))).unwrap();
+        let data = format!("{}\nnot a change\n\n{}\n", change, change.replace("\"gh\"", "\"gl\""));
+
+        let changes = parse_changes(&data);
+        let ids: Vec<(usize, &str)> = changes.iter().map(|(id, c)| (*id, c.key.as_str())).collect();
+        assert_eq!(ids, [(1, "gh"), (4, "gl")]);
+    }
+}
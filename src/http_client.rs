@@ -0,0 +1,389 @@
+// A small blocking HTTP/1.1 client for the link checker and the CLI: one request per
+// connection, http and https (rustls with the webpki roots), redirects are returned rather
+// than followed.
+use rustls::{ClientConfig, ClientConnection, OwnedTrustAnchor, RootCertStore, ServerName, StreamOwned};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+// Longest status or header line accepted, and how many headers
+const MAX_LINE: u64 = 16 * 1024;
+const MAX_HEADERS: usize = 100;
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    // The first header called `name` (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+// Sends one request and reads the whole response
+pub fn send(method: &str, url: &str, headers: &[(&str, &str)], body: Option<&[u8]>, timeout: Duration) -> Result<Response, String> {
+    exchange(method, url, headers, body, timeout, true)
+}
+
+// Sends a body-less request and stops after the response headers (the body is never downloaded)
+pub fn status_only(method: &str, url: &str, timeout: Duration) -> Result<Response, String> {
+    exchange(method, url, &[], None, timeout, false)
+}
+
+// The parts of an http(s) URL needed to make the request
+struct Target<'a> {
+    https: bool,
+    // host[:port] as written, for the Host header
+    authority: &'a str,
+    host: &'a str,
+    port: u16,
+    // Path and query, at least "/"
+    path: String,
+}
+
+fn parse_url(url: &str) -> Result<Target<'_>, String> {
+    let (https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err(format!("Unsupported URL (only http and https): {}", url));
+    };
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    // Credentials in the URL aren't sent
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+
+    // [::1]:8080 or example.com:8080
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(v6) => {
+            let (host, after) = v6.split_once(']').ok_or_else(|| format!("Invalid URL: {}", url))?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().map_err(|_| format!("Invalid port in URL: {}", url))?,
+        None if https => 443,
+        None => 80,
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid URL: {}", url));
+    }
+    let path = if path.starts_with('/') { path.to_string() } else { format!("/{}", path) };
+    Ok(Target { https, authority, host, port, path })
+}
+
+// `location` (a Location header) as an absolute URL, relative to the request `url`
+pub fn join_url(url: &str, location: &str) -> String {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return location.to_string();
+    }
+    let scheme_end = url.find("://").map_or(0, |i| i + 3);
+    if location.starts_with("//") {
+        return format!("{}{}", &url[..scheme_end - 2], location);
+    }
+    let without_fragment = url.split('#').next().unwrap_or(url);
+    let path_start = without_fragment[scheme_end..].find(['/', '?']).map_or(without_fragment.len(), |i| i + scheme_end);
+    let origin = &without_fragment[..path_start];
+    if location.starts_with('/') {
+        return format!("{}{}", origin, location);
+    }
+    // Relative to the directory of the request path
+    let path = without_fragment[path_start..].split('?').next().unwrap_or_default();
+    let directory = path.rfind('/').map_or("/", |i| &path[..=i]);
+    format!("{}{}{}", origin, directory, location)
+}
+
+fn tls_config() -> Arc<ClientConfig> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            let mut roots = RootCertStore::empty();
+            roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject, anchor.spki, anchor.name_constraints)
+            }));
+            Arc::new(ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth())
+        })
+        .clone()
+}
+
+trait Connection: Read + Write {}
+impl<T: Read + Write> Connection for T {}
+
+fn connect(target: &Target, deadline: Instant) -> Result<(Box<dyn Connection>, TcpStream), String> {
+    let addresses = (target.host, target.port)
+        .to_socket_addrs()
+        .map_err(|_| format!("Could not resolve host: {}", target.host))?;
+
+    let mut last_error = format!("Could not resolve host: {}", target.host);
+    for address in addresses {
+        let remaining = remaining(deadline)?;
+        match TcpStream::connect_timeout(&address, remaining) {
+            Ok(socket) => {
+                let handle = socket.try_clone().map_err(|e| e.to_string())?;
+                if !target.https {
+                    return Ok((Box::new(socket), handle));
+                }
+                let name = ServerName::try_from(target.host).map_err(|_| format!("Invalid host name: {}", target.host))?;
+                let tls = ClientConnection::new(tls_config(), name).map_err(|e| format!("TLS error: {}", e))?;
+                return Ok((Box::new(StreamOwned::new(tls, socket)), handle));
+            }
+            Err(e) => last_error = format!("Failed to connect to {} port {}: {}", target.host, target.port, describe(&e)),
+        }
+    }
+    Err(last_error)
+}
+
+fn remaining(deadline: Instant) -> Result<Duration, String> {
+    deadline
+        .checked_duration_since(Instant::now())
+        .filter(|d| !d.is_zero())
+        .ok_or_else(|| "Timed out".to_string())
+}
+
+fn describe(e: &io::Error) -> String {
+    match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => "Timed out".to_string(),
+        _ => e.to_string(),
+    }
+}
+
+fn exchange(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&[u8]>,
+    timeout: Duration,
+    read_body: bool,
+) -> Result<Response, String> {
+    let target = parse_url(url)?;
+    let deadline = Instant::now() + timeout;
+    let (mut stream, socket) = connect(&target, deadline)?;
+    let set_timeouts = |deadline: Instant| -> Result<(), String> {
+        let remaining = remaining(deadline)?;
+        socket.set_read_timeout(Some(remaining)).and_then(|()| socket.set_write_timeout(Some(remaining))).map_err(|e| e.to_string())
+    };
+    set_timeouts(deadline)?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: go_service\r\nAccept: */*\r\nConnection: close\r\n",
+        method, target.path, target.authority
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if let Some(body) = body {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+    let mut data = request.into_bytes();
+    data.extend_from_slice(body.unwrap_or_default());
+    stream.write_all(&data).and_then(|()| stream.flush()).map_err(|e| describe(&e))?;
+
+    let mut reader = BufReader::new(stream);
+    // Interim 1xx responses (100 Continue) come before the real one
+    let (status, headers) = loop {
+        set_timeouts(deadline)?;
+        let (status, headers) = read_head(&mut reader)?;
+        if !(100..200).contains(&status) {
+            break (status, headers);
+        }
+    };
+    let mut response = Response { status, headers, body: Vec::new() };
+    if !read_body || method == "HEAD" || status == 204 || status == 304 {
+        return Ok(response);
+    }
+
+    set_timeouts(deadline)?;
+    let chunked = response.header("transfer-encoding").is_some_and(|t| t.to_ascii_lowercase().contains("chunked"));
+    let length = response.header("content-length").and_then(|l| l.trim().parse::<u64>().ok());
+    response.body = if chunked {
+        read_chunked(&mut reader)?
+    } else if let Some(length) = length {
+        let mut body = Vec::new();
+        reader.take(length).read_to_end(&mut body).map_err(|e| describe(&e))?;
+        body
+    } else {
+        read_to_close(&mut reader)?
+    };
+    Ok(response)
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = Vec::new();
+    reader.by_ref().take(MAX_LINE).read_until(b'\n', &mut line).map_err(|e| describe(&e))?;
+    if line.is_empty() {
+        return Err("Empty reply from server".to_string());
+    }
+    Ok(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+}
+
+// The status code and headers of a response
+fn read_head(reader: &mut impl BufRead) -> Result<(u16, Vec<(String, String)>), String> {
+    let status_line = read_line(reader)?;
+    let status = status_line
+        .strip_prefix("HTTP/")
+        .and_then(|rest| rest.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("Not an HTTP response: {}", status_line))?;
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok((status, headers));
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err("Too many response headers".to_string());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+}
+
+fn read_chunked(reader: &mut impl BufRead) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = u64::from_str_radix(size, 16).map_err(|_| format!("Invalid chunk size: {}", line))?;
+        if size == 0 {
+            return Ok(body);
+        }
+        let mut chunk = Vec::new();
+        reader.by_ref().take(size).read_to_end(&mut chunk).map_err(|e| describe(&e))?;
+        body.extend_from_slice(&chunk);
+        // The CRLF after the chunk
+        read_line(reader)?;
+    }
+}
+
+// A body without a length ends when the server closes the connection
+fn read_to_close(reader: &mut impl Read) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    match reader.read_to_end(&mut body) {
+        Ok(_) => Ok(body),
+        // Some TLS servers close without a close_notify; what arrived is the body
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(body),
+        Err(e) => Err(describe(&e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Answers one connection with `reply` and hands back the request it received
+    fn serve_once(reply: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let n = stream.read(&mut request).unwrap();
+            stream.write_all(reply.as_bytes()).unwrap();
+            String::from_utf8_lossy(&request[..n]).to_string()
+        });
+        (base, handle)
+    }
+
+    #[test]
+    fn parses_urls() {
+        let target = parse_url("https://user:pw@example.com:8443/a/b?q=1#top").unwrap();
+        assert!(target.https);
+        assert_eq!((target.authority, target.host, target.port, target.path.as_str()), ("example.com:8443", "example.com", 8443, "/a/b?q=1"));
+
+        let target = parse_url("http://[::1]?x").unwrap();
+        assert_eq!((target.host, target.port, target.path.as_str()), ("::1", 80, "/?x"));
+        assert_eq!(parse_url("https://example.com").unwrap().port, 443);
+
+        assert!(parse_url("ftp://example.com/").is_err());
+        assert!(parse_url("http://example.com:http/").is_err());
+        assert!(parse_url("http:///path").is_err());
+    }
+
+    #[test]
+    fn joins_redirect_locations() {
+        let url = "https://example.com/docs/page?x=1#f";
+        assert_eq!(join_url(url, "https://other.example/"), "https://other.example/");
+        assert_eq!(join_url(url, "//cdn.example/a"), "https://cdn.example/a");
+        assert_eq!(join_url(url, "/login"), "https://example.com/login");
+        assert_eq!(join_url(url, "next"), "https://example.com/docs/next");
+        assert_eq!(join_url("http://example.com", "next"), "http://example.com/next");
+    }
+
+    #[test]
+    fn skips_interim_responses_and_keeps_headers() {
+        let (base, _) = serve_once("HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 301 Moved Permanently\r\nlocation: /new\r\nContent-Length: 1000\r\n\r\n");
+        let response = status_only("GET", &base, Duration::from_secs(5)).unwrap();
+        assert_eq!(response.status, 301);
+        assert_eq!(response.header("Location"), Some("/new"));
+    }
+
+    #[test]
+    fn sends_headers_and_body_and_reads_the_reply() {
+        let (base, server) = serve_once("HTTP/1.1 201 Created\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}");
+        let response = send("POST", &format!("{}/api?x=1", base), &[("Authorization", "Bearer t")], Some(b"{\"a\":1}"), Duration::from_secs(5)).unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        assert_eq!(response.text(), "{}");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /api?x=1 HTTP/1.1\r\n"), "{}", request);
+        assert!(request.contains("\r\nAuthorization: Bearer t\r\n"));
+        assert!(request.contains("\r\nContent-Length: 7\r\n"));
+        assert!(request.ends_with("\r\n\r\n{\"a\":1}"));
+    }
+
+    #[test]
+    fn reads_chunked_and_unsized_bodies() {
+        let (base, _) = serve_once("HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;x=y\r\n world\r\n0\r\n\r\n");
+        let response = send("GET", &base, &[], None, Duration::from_secs(5)).unwrap();
+        assert_eq!((response.status, response.text().as_str()), (200, "hello world"));
+
+        let (base, _) = serve_once("HTTP/1.0 404 Not Found\r\n\r\nmissing");
+        let response = send("GET", &base, &[], None, Duration::from_secs(5)).unwrap();
+        assert_eq!((response.status, response.text().as_str()), (404, "missing"));
+    }
+
+    #[test]
+    fn reports_unreachable_and_garbled_servers() {
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let error = status_only("HEAD", &format!("http://{}/", addr), Duration::from_secs(5)).unwrap_err();
+        assert!(error.starts_with("Failed to connect to 127.0.0.1"), "{}", error);
+
+        let (base, _) = serve_once("SSH-2.0-OpenSSH\r\n");
+        assert!(status_only("HEAD", &base, Duration::from_secs(5)).unwrap_err().starts_with("Not an HTTP response"));
+    }
+
+    #[test]
+    fn times_out_on_a_silent_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let started = Instant::now();
+        assert_eq!(status_only("HEAD", &url, Duration::from_millis(300)).unwrap_err(), "Timed out");
+        assert!(started.elapsed() < Duration::from_secs(3));
+        drop(listener);
+    }
+}
@@ -8,10 +8,30 @@ mod calculator; // NEW: Module for the calculator page
 mod paint;      // NEW: Module for the paint tool
 mod request;    // NEW: Module for request builder
 mod board;      // NEW: Module for Task Board
+mod resolve;    // NEW: Shortcut resolution helpers (templates, query merging)
+mod clock;      // NEW: Date helpers for shortcut features
+mod stats;      // NEW: Shortcut usage stats and /stats page
+mod rules;      // NEW: Regex and prefix rule shortcuts
+mod search;     // NEW: OpenSearch descriptor and /search endpoint
+mod bookmarks;  // NEW: Netscape bookmark import/export for shortcuts
+mod watcher;    // NEW: Reloads shortcut files when they change on disk
+mod health;     // NEW: Background link health checker for shortcut destinations
+mod api;        // NEW: JSON REST API for shortcuts
+mod cli;        // NEW: Command-line subcommands (add, list, rm, resolve, import, export)
+mod config;     // NEW: Bind address, port, data directory and store file paths
+mod auth;       // NEW: Optional password/token authentication for every route
+mod csrf;       // NEW: CSRF tokens and Origin checks for state-changing requests
+mod hidden;     // NEW: Passphrase-locked /hidden page and locked shortcut redirects
+mod history;    // NEW: Shortcut change log with revert and point-in-time restore
+mod variables;  // NEW: {today}, {user} and other variables in shortcut URLs
+mod qr;         // NEW: QR code generator (SVG) for the link preview
+mod preview;    // NEW: `alias+` link preview page
+mod http_client; // NEW: Blocking HTTP(S) client for link checks and the CLI
 
 use actix_files::Files;
 use actix_web::{
     get, 
+    middleware,
     web::{self, Data}, 
     App, HttpResponse, HttpServer, Responder,
 };
@@ -28,36 +48,38 @@ use calculator::calculator_get;
 use paint::paint_get; 
 use request::{request_get, request_save, request_delete, request_run};
 // FIX: Import all board handlers including the new reorder handler
+use stats::stats_get;
+use health::health_check_now;
+use api::{api_list, api_get, api_create, api_update, api_delete, api_resolve};
+use auth::{login_get, login_post, logout};
+use hidden::{hidden_get, hidden_unlock, hidden_lock, hidden_edit, hidden_delete};
+use history::{history_get, history_revert, history_restore};
+use rules::{rule_add, rule_delete, rule_move};
+use search::{opensearch_get, search_get};
+use bookmarks::{shortcuts_export, shortcuts_import_get, shortcuts_import_preview, shortcuts_import};
 use board::{board_get, board_data_get, board_add_column, board_delete_column, board_save_task, board_move_task, board_delete_task, board_reorder_columns};
 
 use elements::theme::{get_settings, save_theme};
-use elements::shortcut::{add_shortcut, delete_shortcut}; 
-use base_page::{render_base_page, render_add_shortcut_button, render_add_shortcut_modal, nav_bar_html};
-use not_found::{go, render_shortcuts_table}; 
-
-static SHORTCUTS_FILE: &str = "shortcuts.json";
-static HIDDEN_SHORTCUTS_FILE: &str = "hidden-shortcuts.json";
-static WORK_SHORTCUTS_FILE: &str = "work-shortcuts.json"; 
-static NOTES_FILE: &str = "notes.json";
-
-// Only shortcut loading remains here
-fn load_shortcuts(path: &str) -> std::io::Result<HashMap<String, String>> {
-    let data = fs::read_to_string(path)?;
-    let map: HashMap<String, String> = serde_json::from_str(&data)?;
-    Ok(map)
-}
+use elements::shortcut::{add_shortcut, edit_shortcut, delete_shortcut, delete_expired, save_shortcut_settings, load_collections, log_collisions, load_collections_config, default_collections}; 
+use base_page::{render_base_page, render_add_shortcut_button, render_add_shortcut_modal, render_edit_shortcut_modal, nav_bar_html};
+use not_found::{go, render_collision_banner, render_expired_shortcuts, render_load_errors, render_shortcuts_table}; 
+use config::files;
 
 #[get("/")]
 async fn index(state: Data<Arc<AppState>>) -> impl Responder {
-    let shortcuts = state.shortcuts.lock().unwrap();
-    let work_shortcuts = state.work_shortcuts.lock().unwrap(); 
+    let collections = state.collections.lock().unwrap();
     let current_theme = state.current_theme.lock().unwrap(); 
+    let health = state.health.lock().unwrap();
 
-    // Combine all *visible* shortcuts for display on the home page
-    let mut combined_shortcuts = shortcuts.clone();
-    combined_shortcuts.extend(work_shortcuts.clone());
-
-    let table_html = render_shortcuts_table(&combined_shortcuts);
+    // One table per *visible* collection, with warnings for files that failed to load
+    // and aliases that can't be reached, followed by expired aliases waiting for cleanup
+    let table_html = format!(
+        "{}{}{}{}",
+        render_load_errors(&collections),
+        render_collision_banner(&collections),
+        render_shortcuts_table(&collections, &health),
+        render_expired_shortcuts(&collections)
+    );
     
     // 1. Create the CUSTOM navigation bar with the Add Shortcut button injected into its placeholder.
     let nav_with_button = nav_bar_html()
@@ -65,7 +87,7 @@ async fn index(state: Data<Arc<AppState>>) -> impl Responder {
     
     let content = format!(
         r#"
-        <p>Type a shortcut key into the URL bar (e.g., <code>/gh</code>) to go directly to the destination.</p>
+        <p>Type a shortcut key into the URL bar (e.g., <code>/gh</code>) to go directly to the destination, or add a <code>+</code> (<code>/gh+</code>) to see where it goes first. Deleted or changed something by mistake? Undo it from the <a href="/shortcuts/history">history</a>.</p>
         {}
         "#,
         table_html
@@ -76,60 +98,79 @@ async fn index(state: Data<Arc<AppState>>) -> impl Responder {
     
     let final_html = html_output
         .replace(&nav_bar_html(), &nav_with_button)
-        .replace("</body>", &format!("{}</body>", render_add_shortcut_modal()));
+        .replace("</body>", &format!("{}{}</body>", render_add_shortcut_modal(&collections), render_edit_shortcut_modal()));
 
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(final_html)
 }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    
+/// Loads every store from its JSON file (see config.rs for where those live).
+/// Shared by the server and the file-based CLI commands.
+pub fn load_app_state() -> AppState {
+    let files = files();
+
     // --- Shortcut Loading ---
-    let shortcuts = load_shortcuts(SHORTCUTS_FILE).unwrap_or_else(|e| {
-        eprintln!("Failed to load {SHORTCUTS_FILE}: {e}"); 
-        HashMap::new()
+    // collections.json lists the shortcut files and their priority; without it the
+    // three original files (shortcuts, hidden, work) are used.
+    let collection_configs = load_collections_config(&files.collections).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}. Using default collections.", files.collections);
+        default_collections()
+    });
+
+    let collections = load_collections(&collection_configs);
+
+    let go_settings = elements::shortcut::load_go_settings(&files.go_settings).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}. Using default shortcut settings.", files.go_settings);
+        app_state::GoSettings::default()
     });
 
-    let hidden_shortcuts = load_shortcuts(HIDDEN_SHORTCUTS_FILE).unwrap_or_else(|e| {
-        eprintln!("Failed to load {HIDDEN_SHORTCUTS_FILE}: {e}");
+    let shortcut_stats = stats::load_stats(&files.stats).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}", files.stats);
         HashMap::new()
     });
 
-    let work_shortcuts = load_shortcuts(WORK_SHORTCUTS_FILE).unwrap_or_else(|e| {
-        eprintln!("Failed to load {WORK_SHORTCUTS_FILE}: {e}");
+    let link_health = health::load_health(&files.health).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}", files.health);
         HashMap::new()
     });
 
+    let shortcut_rules = rules::load_rules(&files.rules).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}", files.rules);
+        Vec::new()
+    });
+
     // --- Notes Loading ---
-    let notes_vec = if Path::new(NOTES_FILE).exists() {
-        let data = fs::read_to_string(NOTES_FILE).unwrap_or_else(|_| "[]".into());
+    let notes_vec = if Path::new(&files.notes).exists() {
+        let data = fs::read_to_string(&files.notes).unwrap_or_else(|_| "[]".into());
         serde_json::from_str(&data).unwrap_or_else(|_| Vec::new())
     } else {
         Vec::new()
     };
 
     // --- Theme Loading ---
-    let saved_themes = elements::theme::load_themes("themes.json").unwrap_or_else(|e| {
-        eprintln!("Failed to load themes.json: {e}. Creating default map.");
+    let saved_themes = elements::theme::load_themes(&files.themes).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}. Creating default map.", files.themes);
         let mut map = HashMap::new();
         let default = elements::theme::default_dark_theme();
         map.insert(default.name.clone(), default);
         map
     });
 
-    let current_theme = elements::theme::load_current_theme("current_theme.json").unwrap_or_else(|e| {
-        eprintln!("Failed to load current_theme.json: {e}. Using default theme.");
+    let current_theme = elements::theme::load_current_theme(&files.current_theme).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {e}. Using default theme.", files.current_theme);
         saved_themes.get("Dark Default").cloned().unwrap_or_else(elements::theme::default_dark_theme)
     });
 
 
-    // Shared application state
-    let state = Arc::new(AppState {
-        shortcuts: Mutex::new(shortcuts),
-        hidden_shortcuts: Mutex::new(hidden_shortcuts),
-        work_shortcuts: Mutex::new(work_shortcuts),
+    AppState {
+        collections: Mutex::new(collections),
+        go_settings: Mutex::new(go_settings),
+        rules: Mutex::new(shortcut_rules),
+        stats: Mutex::new(shortcut_stats),
+        health: Mutex::new(link_health),
+        sessions: Mutex::new(HashMap::new()),
+        unlocked: Mutex::new(HashMap::new()),
         notes: Mutex::new(notes_vec),
 
         // THEME STATE
@@ -139,12 +180,54 @@ async fn main() -> std::io::Result<()> {
         // SQL service state
         connections: Mutex::new(Vec::new()),
         last_results: Mutex::new(Vec::new()),
-    });
+    }
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // Configuration flags (--port, --data-dir, ...) apply to every command
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (settings, args) = match config::load(&args) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("go_service: {}", e);
+            std::process::exit(2);
+        }
+    };
+    config::init(settings);
+
+    // `go_service add ...`, `list`, etc. run a single command and exit; no arguments (or
+    // `serve`) starts the server
+    if !(args.is_empty() || args == ["serve"]) {
+        std::process::exit(cli::run(&args));
+    }
+
+    let settings = config::config();
+    config::ensure_data_dir()?;
+
+    // Shared application state
+    let state = Arc::new(load_app_state());
+    log_collisions(&state.collections.lock().unwrap());
+
+    // Pick up edits to the shortcut files (e.g. a synced work file) without a restart
+    watcher::spawn_shortcut_watcher(state.clone());
+    // Periodically check that shortcut destinations still respond
+    health::spawn_health_checker(state.clone());
+    // Write the recorded shortcut hits every few seconds instead of on every redirect
+    stats::spawn_stats_writer(state.clone());
+    // Kept for the final save once the server stops
+    let shutdown_state = state.clone();
 
     // Build server
     HttpServer::new(move || {
         App::new()
             .app_data(Data::new(state.clone()))
+            // Rejects cross-site posts; runs after the login check below
+            .wrap(middleware::from_fn(csrf::verify_csrf))
+            // Login or API token check (only when a password or token is configured)
+            .wrap(middleware::from_fn(auth::require_auth))
+            // Bookmark imports post whole browser exports, well past the default form limit
+            .app_data(web::FormConfig::default().limit(csrf::FORM_LIMIT))
             .service(index)
             .service(note_get)
             .service(note_post)
@@ -164,6 +247,8 @@ async fn main() -> std::io::Result<()> {
             .service(board_move_task)
             .service(board_delete_task)
             .service(board_reorder_columns) // NEW: Register reorder handler
+            .service(stats_get)
+            .service(health_check_now)
             
             .route("/note/delete", web::post().to(note_delete))
             .service(sql::sql_get)
@@ -173,14 +258,47 @@ async fn main() -> std::io::Result<()> {
             .service(sql::sql_view)
             .service(sql::sql_save) 
             .service(sql::sql_delete) 
-            .service(Files::new("/static", "./static").prefer_utf8(true))
+            .service(Files::new("/static", &settings.static_dir).prefer_utf8(true))
             .service(add_shortcut)      
+            .service(edit_shortcut)
             .service(delete_shortcut)   
+            .service(delete_expired)
             .service(get_settings)      
             .service(save_theme)        
+            .service(save_shortcut_settings)
+            .service(rule_add)
+            .service(rule_delete)
+            .service(rule_move)
+            .service(opensearch_get)
+            .service(search_get)
+            .service(shortcuts_export)
+            .service(history_get)
+            .service(history_revert)
+            .service(history_restore)
+            .service(shortcuts_import_get)
+            .service(shortcuts_import_preview)
+            .service(shortcuts_import)
+            .service(api_list)
+            .service(api_get)
+            .service(api_create)
+            .service(api_update)
+            .service(api_delete)
+            .service(api_resolve)
+            .service(login_get)
+            .service(login_post)
+            .service(logout)
+            .service(hidden_get)
+            .service(hidden_unlock)
+            .service(hidden_lock)
+            .service(hidden_edit)
+            .service(hidden_delete)
             .service(go) 
     })
-    .bind(("0.0.0.0", 80))?
+    .bind((settings.bind.as_str(), settings.port))?
     .run()
-    .await
+    .await?;
+
+    // Hits recorded since the last periodic save
+    stats::flush(&shutdown_state);
+    Ok(())
 }
\ No newline at end of file
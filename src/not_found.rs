@@ -1,19 +1,33 @@
-use actix_web::{get, web::{self, Data}, HttpResponse, Responder};
+use actix_web::{get, http::StatusCode, web::{self, Data}, HttpRequest, HttpResponse, Responder};
 use htmlescape::encode_minimal;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 // FIX: Changed to use crate::... imports, removed incorrect mod declarations
-use crate::app_state::AppState;
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::auth;
+use crate::hidden;
+use crate::preview;
+use crate::elements::shortcut::{find_collisions, visible_shortcuts};
+use crate::clock::{current_ts, datetime_input_value, format_date, format_datetime};
+use crate::health::LinkHealth;
 use crate::app_state::Theme; // Needed for not_found_page signature
+use crate::resolve::{self, encode_capture, ResolveError, Resolver};
+use crate::stats;
+use crate::variables::VariableContext;
 // Import rendering helpers
-use crate::base_page::{render_base_page, render_add_shortcut_button, render_add_shortcut_modal, nav_bar_html};
+use crate::base_page::{render_base_page, render_add_shortcut_button, render_add_shortcut_modal, render_edit_shortcut_modal, nav_bar_html};
 
-/// Builds HTML table rows of shortcuts, grouped by URL, with inline delete buttons.
-fn grouped_shortcuts_table_with_delete(shortcuts: &HashMap<String, String>) -> String {
-    let mut grouped: HashMap<&str, Vec<&str>> = HashMap::new();
-    for (key, url) in shortcuts.iter() {
-        grouped.entry(url.as_str()).or_default().push(key.as_str());
+/// Builds HTML table rows of a collection's shortcuts, grouped by URL, with inline edit and
+/// delete buttons (or, for read-only fragment collections, the file each key came from).
+/// Descriptions, tags and owners of every key in a group are merged into the row.
+/// Expired shortcuts are left out; they are listed by `render_expired_shortcuts` instead.
+fn grouped_shortcuts_table_with_delete(collection: &ShortcutCollection, health: &HashMap<String, LinkHealth>) -> String {
+    let now = current_ts();
+    let mut grouped: HashMap<&str, Vec<(&str, &Shortcut)>> = HashMap::new();
+    for (key, shortcut) in collection.shortcuts.iter().filter(|(_, s)| !s.is_expired(now)) {
+        grouped.entry(shortcut.url.as_str()).or_default().push((key.as_str(), shortcut));
     }
 
     let mut rows = String::new();
@@ -21,11 +35,25 @@ fn grouped_shortcuts_table_with_delete(shortcuts: &HashMap<String, String>) -> S
     // Sort by URL first
     grouped_vec.sort_by_key(|(url, _)| url.to_owned());
 
-    for (url, mut keys) in grouped_vec {
-        keys.sort();
-        let key_links = keys
+    for (url, mut entries) in grouped_vec {
+        entries.sort_by_key(|(k, _)| *k);
+        let key_links = entries
             .iter()
-            .map(|k| {
+            .map(|(k, shortcut)| {
+                if collection.directory {
+                    let source = collection.sources.get(*k).map(String::as_str).unwrap_or(&collection.file);
+                    let file_name = Path::new(source).file_name().map(|f| f.to_string_lossy()).unwrap_or_default();
+                    return format!(
+                        "<span style='white-space: nowrap;'><a href=\"/{0}\">{0}</a>{3} <small class=\"shortcut-source\" title=\"{1}\">{2}</small></span>",
+                        encode_minimal(k),
+                        encode_minimal(source),
+                        encode_minimal(&file_name),
+                        schedule_badge(shortcut, now)
+                    );
+                }
+
+                let edit_button = edit_shortcut_button(k, shortcut);
+
                 // Delete form for this specific key, styled inline next to the key link
                 let delete_form = format!(
                     r#"
@@ -40,54 +68,366 @@ fn grouped_shortcuts_table_with_delete(shortcuts: &HashMap<String, String>) -> S
                 );
                 
                 // Group the link and the delete button, keeping them on one line
-                format!("<span style='white-space: nowrap;'><a href=\"/{0}\">{0}</a>{1}</span>", encode_minimal(k), delete_form)
+                format!(
+                    "<span style='white-space: nowrap;'><a href=\"/{0}\">{0}</a>{1}{2}{3}</span>",
+                    encode_minimal(k),
+                    schedule_badge(shortcut, now),
+                    edit_button,
+                    delete_form
+                )
             })
             .collect::<Vec<_>>()
             .join(" , "); // Join all key spans with a comma space
 
+        // Merge the metadata of all keys pointing at this URL
+        let mut descriptions: Vec<&str> = Vec::new();
+        let mut notes: Vec<&str> = Vec::new();
+        let mut tags: Vec<&str> = Vec::new();
+        let mut owners: Vec<&str> = Vec::new();
+        for (_, s) in &entries {
+            for (list, value) in [(&mut descriptions, s.description.as_str()), (&mut notes, s.notes.as_str()), (&mut owners, s.owner.as_str())] {
+                if !value.is_empty() && !list.contains(&value) {
+                    list.push(value);
+                }
+            }
+            for tag in &s.tags {
+                if !tags.contains(&tag.as_str()) {
+                    tags.push(tag);
+                }
+            }
+        }
+        tags.sort();
+        let updated = entries.iter().map(|(_, s)| s.updated_at).max().unwrap_or(0);
+        let created = entries.iter().map(|(_, s)| s.created_at).filter(|t| *t != 0).min().unwrap_or(0);
+
+        let tag_html = tags
+            .iter()
+            .map(|t| format!("<span class=\"tag\" data-tag=\"{0}\">{0}</span>", encode_minimal(t)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let notes_html = if notes.is_empty() {
+            String::new()
+        } else {
+            format!("<div class=\"shortcut-notes\">{}</div>", encode_minimal(&notes.join(" / ")))
+        };
+        let updated_html = if updated == 0 {
+            String::new()
+        } else {
+            format!(
+                "<span title=\"Created {}\">{}</span>",
+                if created == 0 { "unknown".to_string() } else { format_date(created) },
+                format_date(updated)
+            )
+        };
+
         rows.push_str(&format!(
-            "<tr><td class=\"keys\">{}</td><td class=\"url\">{}</td></tr>",
+            "<tr class=\"shortcut-row\" data-search=\"{}\" data-tags=\"{}\" data-owner=\"{}\"><td class=\"keys\">{}</td><td class=\"url\">{}{}</td><td>{}{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            encode_minimal(&format!(
+                "{} {} {} {} {} {}",
+                entries.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(" "),
+                url,
+                descriptions.join(" "),
+                notes.join(" "),
+                tags.join(" "),
+                owners.join(" ")
+            ).to_lowercase()),
+            encode_minimal(&tags.join(",").to_lowercase()),
+            encode_minimal(&owners.join(",").to_lowercase()),
             key_links,
-            encode_minimal(url)
+            encode_minimal(url),
+            health_flag(health.get(url)),
+            encode_minimal(&descriptions.join(" / ")),
+            notes_html,
+            tag_html,
+            encode_minimal(&owners.join(", ")),
+            updated_html
         ));
     }
     rows
 }
 
-/// Renders the HTML table of shortcuts (reused by home and 404 pages).
-// FIX: Made function public for external use (E0603)
-pub fn render_shortcuts_table(shortcuts: &HashMap<String, String>) -> String {
-    // Use the grouping function with inline delete buttons
-    let rows = grouped_shortcuts_table_with_delete(shortcuts); 
+/// Pencil button carrying a shortcut's current values, which opens and prefills the edit modal
+/// (see `render_edit_shortcut_modal`).
+pub fn edit_shortcut_button(key: &str, shortcut: &Shortcut) -> String {
     format!(
-        r#"
-    <table class="grid">
+        r#"<button type="button" class="edit-shortcut-btn" title="Edit {}" {} style="background: none; border: none; color: var(--link-color); padding: 0; cursor: pointer; margin: 0 0 0 5px; font-size: 10px; line-height: 1;">&#9998;</button>"#,
+        encode_minimal(key),
+        edit_shortcut_data(key, shortcut)
+    )
+}
+
+/// The data-* attributes the edit modal reads its values from.
+pub fn edit_shortcut_data(key: &str, shortcut: &Shortcut) -> String {
+    format!(
+        r#"data-key="{0}" data-url="{1}" data-description="{2}" data-tags="{3}" data-owner="{4}" data-notes="{5}" data-active-from="{6}" data-expires-at="{7}""#,
+        encode_minimal(key),
+        encode_minimal(&shortcut.url),
+        encode_minimal(&shortcut.description),
+        encode_minimal(&shortcut.tags.join(", ")),
+        encode_minimal(&shortcut.owner),
+        encode_minimal(&shortcut.notes),
+        datetime_input_value(shortcut.active_from),
+        datetime_input_value(shortcut.expires_at)
+    )
+}
+
+/// Marks an alias that isn't active yet or that will expire.
+fn schedule_badge(shortcut: &Shortcut, now: u64) -> String {
+    let (label, ts) = if shortcut.active_from > now {
+        ("starts", shortcut.active_from)
+    } else if shortcut.expires_at != 0 {
+        ("expires", shortcut.expires_at)
+    } else {
+        return String::new();
+    };
+    format!(
+        " <small class=\"schedule-badge schedule-{0}\" title=\"{0} {1} UTC\">{0} {2}</small>",
+        label,
+        format_datetime(ts),
+        format_date(ts)
+    )
+}
+
+/// Marks a destination the last link check found broken or redirecting.
+fn health_flag(health: Option<&LinkHealth>) -> String {
+    let Some(h) = health.filter(|h| h.is_broken() || h.is_redirect()) else {
+        return String::new();
+    };
+    let (class, label) = if h.is_broken() { ("link-broken", "broken") } else { ("link-redirect", "redirects") };
+    format!(
+        " <span class=\"link-health {}\" title=\"{} {} at {} UTC\">{}</span>",
+        class,
+        h.method,
+        encode_minimal(&h.summary()),
+        format_datetime(h.checked_at),
+        label
+    )
+}
+
+/// Renders the HTML tables of shortcuts (reused by home and 404 pages), one per visible
+/// collection, with a filter box that matches any field. `tag:name` and `owner:name` terms
+/// filter on those fields only.
+// FIX: Made function public for external use (E0603)
+pub fn render_shortcuts_table(collections: &[ShortcutCollection], health: &HashMap<String, LinkHealth>) -> String {
+    let tables = collections
+        .iter()
+        .filter(|c| !c.hidden)
+        .map(|c| {
+            // Use the grouping function with inline delete buttons
+            let rows = grouped_shortcuts_table_with_delete(c, health);
+            format!(
+                r#"
+    <h2 class="collection-heading">{name} <small>({file}, priority {priority}, {count}{read_only})</small></h2>
+    <table class="grid shortcuts-table">
       <thead>
-        <tr><th>Shortcut Keys</th><th>Destination URL</th></tr>
+        <tr><th>Shortcut Keys</th><th>Destination URL</th><th>Description</th><th>Tags</th><th>Owner</th><th>Updated</th></tr>
       </thead>
       <tbody>
         {rows}
       </tbody>
     </table>
     "#,
+                name = encode_minimal(&c.name),
+                file = encode_minimal(&c.file),
+                priority = c.priority,
+                count = c.shortcuts.len(),
+                read_only = if c.directory { ", read-only" } else { "" },
+                rows = rows
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+    <input type="text" id="shortcut-filter" class="shortcut-filter" placeholder="Filter shortcuts (text, tag:name, owner:name)">
+    {tables}
+    <script>
+      (function() {{
+        const input = document.getElementById('shortcut-filter');
+        const rows = document.querySelectorAll('.shortcuts-table tr.shortcut-row');
+        const applyFilter = () => {{
+          const terms = input.value.toLowerCase().split(/\s+/).filter(t => t);
+          rows.forEach(row => {{
+            const visible = terms.every(term => {{
+              if (term.startsWith('tag:')) return row.dataset.tags.split(',').includes(term.slice(4));
+              if (term.startsWith('owner:')) return row.dataset.owner.includes(term.slice(6));
+              return row.dataset.search.includes(term);
+            }});
+            row.style.display = visible ? '' : 'none';
+          }});
+        }};
+        input.addEventListener('input', applyFilter);
+        // Clicking a tag filters by it
+        document.querySelectorAll('.shortcuts-table .tag').forEach(tag => {{
+          tag.addEventListener('click', () => {{
+            input.value = 'tag:' + tag.dataset.tag.toLowerCase();
+            applyFilter();
+          }});
+        }});
+      }})();
+    </script>
+    "#,
+        tables = tables
+    )
+}
+
+/// Lists expired shortcuts of the visible collections so they can be cleaned up, with a
+/// delete button per alias and one for all of them. Empty if nothing has expired.
+pub fn render_expired_shortcuts(collections: &[ShortcutCollection]) -> String {
+    let now = current_ts();
+    let mut expired: Vec<(&ShortcutCollection, &String, &Shortcut)> = collections
+        .iter()
+        .filter(|c| !c.hidden)
+        .flat_map(|c| c.shortcuts.iter().map(move |(k, s)| (c, k, s)))
+        .filter(|(_, _, s)| s.is_expired(now))
+        .collect();
+    if expired.is_empty() {
+        return String::new();
+    }
+    expired.sort_by_key(|(_, k, s)| (s.expires_at, *k));
+
+    let rows = expired
+        .iter()
+        .map(|(c, k, s)| {
+            // Fragment files are edited in the repository they come from, not here
+            let action = if c.directory {
+                let source = c.sources.get(*k).map(String::as_str).unwrap_or(&c.file);
+                format!("<small class=\"shortcut-source\">{}</small>", encode_minimal(source))
+            } else {
+                format!(
+                    r#"<form action="/delete_shortcut" method="POST" style="display:inline;"><input type="hidden" name="key" value="{}"><button type="submit" class="delete-button">Delete</button></form>"#,
+                    encode_minimal(k)
+                )
+            };
+            format!(
+                "<tr><td>{}</td><td class=\"url\">{}</td><td>{}</td><td>{} UTC</td><td>{}</td></tr>",
+                encode_minimal(k),
+                encode_minimal(&s.url),
+                encode_minimal(&c.name),
+                format_datetime(s.expires_at),
+                action
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+    <details class="expired-shortcuts">
+        <summary>Expired shortcuts ({count})</summary>
+        <p>These aliases no longer redirect. Delete them to keep the list tidy; deletions can be undone from the <a href="/shortcuts/history">history</a>.</p>
+        <table class="grid">
+          <thead>
+            <tr><th>Shortcut</th><th>Destination URL</th><th>Collection</th><th>Expired</th><th></th></tr>
+          </thead>
+          <tbody>
+            {rows}
+          </tbody>
+        </table>
+        <form action="/shortcuts/expired/delete" method="POST" onsubmit="return confirm('Delete all expired shortcuts?');">
+            <button type="submit" class="form-submit-btn">Delete All Expired</button>
+        </form>
+    </details>
+    "#,
+        count = expired.len(),
         rows = rows
     )
 }
 
-/// Render the 404 page with available shortcuts
-pub fn not_found_page(shortcuts: &HashMap<String, String>, current_theme: &Theme) -> String {
-    let table = render_shortcuts_table(shortcuts);
+// Lists shortcut files whose latest version on disk couldn't be loaded
+pub fn render_load_errors(collections: &[ShortcutCollection]) -> String {
+    let items = collections
+        .iter()
+        .filter_map(|c| {
+            c.load_error.as_ref().map(|e| {
+                format!(
+                    "<li><code>{}</code> ({}): {}</li>",
+                    encode_minimal(&c.file),
+                    encode_minimal(&c.name),
+                    encode_minimal(e)
+                )
+            })
+        })
+        .collect::<String>();
+    if items.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        r#"
+    <div class="warning-banner">
+        <strong>Some shortcut files could not be loaded.</strong> The last good version of each is still in use until the file is fixed.
+        <ul>{}</ul>
+    </div>
+    "#,
+        items
+    )
+}
+
+/// Renders a warning listing aliases that exist in more than one collection, naming the
+/// collection that wins and the ones whose entry can never be reached. Empty if there are none.
+pub fn render_collision_banner(collections: &[ShortcutCollection]) -> String {
+    let collisions = find_collisions(collections);
+    if collisions.is_empty() {
+        return String::new();
+    }
+
+    let items = collisions
+        .iter()
+        .map(|c| {
+            format!(
+                "<li><code>{}</code>: {} wins, shadowed in {}</li>",
+                encode_minimal(&c.key),
+                encode_minimal(&c.winner),
+                encode_minimal(&c.shadowed.join(", "))
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+    <div class="warning-banner">
+        <strong>{} shadowed shortcut(s).</strong> These aliases exist in more than one collection; only the highest priority one is used.
+        <ul>{}</ul>
+    </div>
+    "#,
+        collisions.len(),
+        items
+    )
+}
+
+/// Render the 404 page with "did you mean" suggestions and the available shortcuts.
+/// `suggestions` holds (alias, link) pairs, where the link keeps the rest of the requested path.
+pub fn not_found_page(
+    collections: &[ShortcutCollection],
+    health: &HashMap<String, LinkHealth>,
+    suggestions: &[(String, String)],
+    current_theme: &Theme,
+) -> String {
+    let table = render_shortcuts_table(collections, health);
     
     // Create the CUSTOM navigation bar with the Add Shortcut button injected
     let nav_with_button = nav_bar_html()
         .replace(r#"<div id="optional-button-placeholder"></div>"#, &render_add_shortcut_button());
 
+    let suggestions_html = if suggestions.is_empty() {
+        String::new()
+    } else {
+        let links = suggestions
+            .iter()
+            .map(|(key, link)| format!("<a href=\"{}\">{}</a>", encode_minimal(link), encode_minimal(key)))
+            .collect::<Vec<_>>()
+            .join(" , ");
+        format!("<p class=\"did-you-mean\" style=\"margin: 10px;\">Did you mean: {}?</p>", links)
+    };
+
     let content = format!(
         r#"
     <h1>404 – Shortcut Not Found</h1>
+    {}
     <p>The requested shortcut was not found. Here are your available shortcuts:</p>
     {}
     "#,
+        suggestions_html,
         table
     );
     
@@ -96,61 +436,117 @@ pub fn not_found_page(shortcuts: &HashMap<String, String>, current_theme: &Theme
             // 1. Swap the navigation placeholder with the nav + button
             .replace(&nav_bar_html(), &nav_with_button)
             // 2. Append the modal just before </body>
-            .replace("</body>", &format!("{}</body>", render_add_shortcut_modal()))
+            .replace("</body>", &format!("{}{}</body>", render_add_shortcut_modal(collections), render_edit_shortcut_modal()))
 }
 
 /// Catch‑all route for shortcuts
 /// Updated to capture the full path (including slashes) using {tail:.*}
 #[get("/{tail:.*}")]
 // FIX: Made function public for external use (E0603)
-pub async fn go(path: web::Path<String>, state: Data<Arc<AppState>>) -> impl Responder {
+pub async fn go(req: HttpRequest, path: web::Path<String>, state: Data<Arc<AppState>>) -> impl Responder {
     // The path here captures everything after the domain, e.g. "youtube/omegagiven"
     let req_path = path.into_inner();
+
+    // Browsers re-apply a typed `#fragment` to the redirect themselves, but an encoded one
+    // (`%23section`) arrives as part of the path and has to be carried over explicitly.
+    let (req_path, fragment) = match req_path.split_once('#') {
+        Some((p, f)) => (p.to_string(), Some(f.to_string())),
+        None => (req_path, None),
+    };
+    // `alias+` shows where the shortcut goes instead of redirecting
+    if let Some(response) = preview::preview(&req, &state, &req_path) {
+        return response;
+    }
+    let mut query = resolve::parse_query(req.query_string()).unwrap_or_default();
     
     // Lock mutexes to read
-    let shortcuts = state.shortcuts.lock().unwrap();
-    let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
-    let work_shortcuts = state.work_shortcuts.lock().unwrap(); 
+    let collections = state.collections.lock().unwrap();
+    let rules = state.rules.lock().unwrap();
+    let go_settings = state.go_settings.lock().unwrap();
     let current_theme = state.current_theme.lock().unwrap(); // Get current theme
 
-    // Helper to find a URL in any of the maps
-    let find_url = |key: &str| -> Option<String> {
-        shortcuts.get(key)
-            .or_else(|| hidden_shortcuts.get(key))
-            .or_else(|| work_shortcuts.get(key))
-            .cloned()
+    // 1-3. Exact match, rules and smart append, following `go:` alias chains
+    let resolver = Resolver {
+        collections: &collections,
+        rules: &rules,
+        settings: &go_settings,
+        variables: VariableContext::from_request(&req),
     };
-
-    // 1. Exact Match: Check if the full path is a defined shortcut
-    if let Some(url) = find_url(&req_path) {
-        return HttpResponse::Found()
-            .append_header(("Location", url))
-            .finish();
-    }
-
-    // 2. Smart Append: Check if the first segment is a shortcut (e.g. "youtube/omegagiven")
-    // This splits "youtube/omegagiven" into "youtube" and "omegagiven"
-    if let Some((alias, remainder)) = req_path.split_once('/') {
-        if let Some(base_url) = find_url(alias) {
-            // If the base URL ends with '/', just append. Otherwise add '/' then append.
-            let new_url = if base_url.ends_with('/') {
-                format!("{}{}", base_url, remainder)
-            } else {
-                format!("{}/{}", base_url, remainder)
-            };
-            
+    match resolver.resolve(&req_path, &mut query) {
+        Ok(resolved) => {
+            if resolved.locked && !hidden::is_unlocked(&req, &state) {
+                return hidden::unlock_redirect(req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/"));
+            }
+            // Done with the shortcuts; don't hold their locks while counting the hit
+            drop(go_settings);
+            drop(rules);
+            drop(collections);
+            drop(current_theme);
+            if let Some(alias) = &resolved.alias {
+                stats::record_hit(&state, alias);
+            }
             return HttpResponse::Found()
-                .append_header(("Location", new_url))
+                .append_header(("Location", resolve::merge_query(&resolved.url, &query, fragment.as_deref())))
                 .finish();
         }
+        Err(ResolveError::NotFound) => {}
+        Err(e) => {
+            // A broken chain is a configuration problem, so say so instead of showing the 404 page
+            let content = format!(
+                "<h1>Shortcut Chain Error</h1><p style=\"margin: 10px;\">{}</p>",
+                encode_minimal(&e.describe())
+            );
+            return HttpResponse::build(StatusCode::LOOP_DETECTED)
+                .content_type("text/html; charset=utf-8")
+                .body(render_base_page("Shortcut Chain Error", &content, &current_theme));
+        }
     }
 
-    // 3. Not Found
-    // Combine all *visible* shortcuts for display on the 404 page
-    let mut combined_shortcuts = shortcuts.clone();
-    combined_shortcuts.extend(work_shortcuts.clone());
+    // 4. Not Found
+    // Redirects may be public (see auth.rs), but the shortcut list and suggestions are not
+    if !auth::is_authenticated(&req) {
+        return HttpResponse::NotFound()
+            .content_type("text/html; charset=utf-8")
+            .body(render_base_page(
+                "Shortcut Not Found",
+                "<h1>Shortcut Not Found</h1><p style=\"margin: 10px;\">No shortcut matches this path. <a href=\"/login\">Log in</a> to see the list.</p>",
+                &current_theme,
+            ));
+    }
+
+    // Combine all *visible* shortcuts for the suggestions. Inactive ones are left out, or an
+    // expired alias would be suggested for (and redirected back to) itself.
+    let mut combined_shortcuts = visible_shortcuts(&collections);
+    let now = current_ts();
+    combined_shortcuts.retain(|_, s| s.is_active(now));
+
+    // 5. Did you mean: rank the missed alias against every visible alias, keeping the rest of
+    // the path and the query so a suggestion behaves like the corrected request would have.
+    let (missed, rest) = match req_path.split_once('/') {
+        Some((alias, remainder)) => (alias, format!("/{}", remainder)),
+        None => (req_path.as_str(), String::new()),
+    };
+    let query_string = match req.query_string() {
+        "" => String::new(),
+        q => format!("?{}", q),
+    };
+    let suggestions = resolve::suggest(missed, combined_shortcuts.keys());
+    // The path was decoded when it was matched, so it is encoded again for the Location header
+    let link_for = |key: &str| format!("/{}{}{}", encode_capture(key), encode_capture(&rest), query_string);
 
+    if let Some(target) = resolve::auto_redirect_target(missed, &suggestions) {
+        return HttpResponse::Found()
+            .append_header(("Location", link_for(&target.key)))
+            .finish();
+    }
+
+    let suggestion_links: Vec<(String, String)> = suggestions
+        .iter()
+        .map(|s| (s.key.clone(), link_for(&s.key)))
+        .collect();
+
+    let health = state.health.lock().unwrap();
     HttpResponse::NotFound()
         .content_type("text/html; charset=utf-8")
-        .body(not_found_page(&combined_shortcuts, &current_theme)) 
-}
\ No newline at end of file
+        .body(not_found_page(&collections, &health, &suggestion_links, &current_theme)) 
+}
@@ -2,12 +2,10 @@ use actix_web::{get, post, web::{self, Data}, HttpResponse, Responder};
 use htmlescape::encode_minimal;
 use serde::Deserialize;
 use std::{fs, io::{self, Write}, sync::Arc};
-use serde_json;
 
 use crate::app_state::{AppState, Theme, Note};
 use crate::base_page::render_base_page;
-
-static NOTES_FILE: &str = "notes.json";
+use crate::config::files;
 
 #[derive(Deserialize)]
 pub struct NoteForm {
@@ -22,7 +20,7 @@ pub struct _DeleteForm {
 
 pub fn save_notes(notes: &[Note]) -> io::Result<()> {
     let json = serde_json::to_string(notes)?;
-    let mut f = fs::File::create(NOTES_FILE)?;
+    let mut f = fs::File::create(&files().notes)?;
     f.write_all(json.as_bytes())?;
     Ok(())
 }
@@ -296,16 +294,15 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
 </script>
 "#;
 
-    let style = format!(
-        r#"
+    let style = r#"
 <style>
-    .toolbar {{
+    .toolbar {
         display: flex;
         gap: 10px;
         margin-bottom: 10px;
         align-items: center;
-    }}
-    .subject-input {{
+    }
+    .subject-input {
         flex-grow: 1;
         padding: 10px;
         border: 1px solid var(--border-color);
@@ -314,24 +311,24 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
         box-sizing: border-box;
         font-size: 1.1em;
         border-radius: 4px;
-    }}
-    .editor-container {{
+    }
+    .editor-container {
         display: flex;
         border: 1px solid var(--border-color);
         border-radius: 4px;
         overflow: hidden;
         margin-bottom: 15px;
         height: 500px; /* Fixed height for scroll sync reliability */
-    }}
+    }
     
     /* Common font settings to ensure alignment */
-    .editor-font {{
+    .editor-font {
         font-family: 'Consolas', 'Monaco', 'Courier New', monospace;
         font-size: 14px;
         line-height: 21px; /* Explicit line height in px */
-    }}
+    }
 
-    .line-numbers {{
+    .line-numbers {
         background-color: var(--tertiary-bg);
         color: #777;
         padding: 10px 5px;
@@ -342,16 +339,16 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
         flex-shrink: 0;
         min-width: 35px;
         box-sizing: border-box;
-    }}
+    }
     
     /* Apply common font class */
-    .line-numbers, #editor {{
+    .line-numbers, #editor {
         font-family: 'Consolas', 'Monaco', 'Courier New', monospace;
         font-size: 14px;
         line-height: 21px; 
-    }}
+    }
 
-    #editor {{
+    #editor {
         flex-grow: 1;
         border: none;
         outline: none;
@@ -362,10 +359,10 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
         background-color: var(--secondary-bg);
         color: var(--text-color);
         box-sizing: border-box;
-    }}
+    }
     
     /* Markdown Preview */
-    #markdown-preview {{
+    #markdown-preview {
         display: none;
         border: 1px solid var(--border-color);
         border-radius: 4px;
@@ -375,14 +372,14 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
         min-height: 400px;
         overflow-y: auto;
         margin-bottom: 15px;
-    }}
-    #markdown-preview h1, #markdown-preview h2 {{ border-bottom: 1px solid var(--border-color); padding-bottom: 5px; }}
-    #markdown-preview code {{ background: #444; padding: 2px 5px; border-radius: 3px; }}
-    #markdown-preview pre {{ background: #333; padding: 10px; border-radius: 5px; overflow-x: auto; }}
-    #markdown-preview blockquote {{ border-left: 3px solid var(--link-color); margin-left: 0; padding-left: 10px; color: #aaa; }}
+    }
+    #markdown-preview h1, #markdown-preview h2 { border-bottom: 1px solid var(--border-color); padding-bottom: 5px; }
+    #markdown-preview code { background: #444; padding: 2px 5px; border-radius: 3px; }
+    #markdown-preview pre { background: #333; padding: 10px; border-radius: 5px; overflow-x: auto; }
+    #markdown-preview blockquote { border-left: 3px solid var(--link-color); margin-left: 0; padding-left: 10px; color: #aaa; }
     
     /* Saved Notes List */
-    .saved-note-item {{
+    .saved-note-item {
         display: flex;
         align-items: center;
         justify-content: space-between;
@@ -390,19 +387,19 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
         background-color: var(--tertiary-bg);
         border-radius: 4px;
         padding: 0 0 0 12px;
-    }}
-    .saved-note {{
+    }
+    .saved-note {
         cursor: pointer;
         padding: 8px 0;
         font-weight: bold;
         transition: color 0.2s;
         flex-grow: 1;
         display: block; 
-    }}
-    .saved-note:hover {{ color: var(--link-hover); }}
+    }
+    .saved-note:hover { color: var(--link-hover); }
     
-    .delete-form {{ margin: 0; line-height: 1; flex-shrink: 0; }}
-    .delete-button {{
+    .delete-form { margin: 0; line-height: 1; flex-shrink: 0; }
+    .delete-button {
         background: none;
         border: none;
         cursor: pointer;
@@ -415,21 +412,20 @@ fn render_note_page(notes: &[Note], current_theme: &Theme) -> String {
         border-bottom-right-radius: 4px;
         line-height: 1;
         margin-top: 0;
-    }}
-    .delete-button:hover {{ background-color: #e00000; color: white; }}
-    .saved-note-item::marker {{ content: ""; }}
+    }
+    .delete-button:hover { background-color: #e00000; color: white; }
+    .saved-note-item::marker { content: ""; }
     
     /* Utility Buttons */
-    .utility-btn {{
+    .utility-btn {
         margin-top: 0;
         margin-right: 5px;
         background-color: var(--tertiary-bg);
         border: 1px solid var(--border-color);
-    }}
-    .utility-btn:hover {{ background-color: var(--border-color); }}
+    }
+    .utility-btn:hover { background-color: var(--border-color); }
 </style>
-        "#,
-    );
+        "#.to_string();
 
     let content = format!(
         r#"
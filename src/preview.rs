@@ -0,0 +1,256 @@
+// Link preview: `go/alias+` shows where a shortcut goes instead of redirecting, with its
+// details, usage, a QR code for opening it on a phone and the edit and delete actions.
+// Handy before following an alias a teammate shared.
+
+use actix_web::{http::StatusCode, web::Data, HttpRequest, HttpResponse};
+use htmlescape::encode_minimal;
+use std::sync::Arc;
+
+use crate::app_state::{AppState, Shortcut, ShortcutCollection};
+use crate::auth;
+use crate::base_page::{render_base_page, render_edit_shortcut_modal};
+use crate::clock::{format_date, format_datetime, SECONDS_IN_DAY};
+use crate::elements::shortcut::{find_active_shortcut, find_shortcut};
+use crate::hidden;
+use crate::not_found::edit_shortcut_data;
+use crate::qr::QrCode;
+use crate::resolve::{self, encode_component, ResolveError, Resolver};
+use crate::stats;
+use crate::variables::VariableContext;
+
+// Renders the preview when `path` ends in `+` and isn't itself an alias (such as `c++`).
+// None lets `go` handle the path as usual, e.g. when nothing matches it.
+pub fn preview(req: &HttpRequest, state: &Data<Arc<AppState>>, path: &str) -> Option<HttpResponse> {
+    let target = path.strip_suffix('+').filter(|t| !t.is_empty())?;
+    let collections = state.collections.lock().unwrap();
+    if find_shortcut(&collections, path).is_some() {
+        return None;
+    }
+
+    // The details aren't public even when redirects are (see auth.rs)
+    if !auth::is_authenticated(req) {
+        return Some(
+            HttpResponse::Found()
+                .append_header(("Location", format!("/login?next={}", encode_component(&format!("/{}", path)))))
+                .finish(),
+        );
+    }
+
+    let rules = state.rules.lock().unwrap();
+    let go_settings = state.go_settings.lock().unwrap();
+    let resolver = Resolver {
+        collections: &collections,
+        rules: &rules,
+        settings: &go_settings,
+        variables: VariableContext::from_request(req),
+    };
+    let mut query = resolve::parse_query(req.query_string()).unwrap_or_default();
+    let now = resolver.variables.now;
+
+    // The alias behind the path; an inactive one doesn't resolve but still has details to show
+    let (destination, alias, locked) = match resolver.resolve(target, &mut query) {
+        Ok(resolved) => (Some(resolve::merge_query(&resolved.url, &query, None)), resolved.alias, resolved.locked),
+        Err(ResolveError::NotFound) => {
+            let key = [target, target.split('/').next().unwrap_or(target)]
+                .into_iter()
+                .find(|k| find_shortcut(&collections, k).is_some())?;
+            let locked = find_shortcut(&collections, key).is_some_and(|(_, s)| s.locked);
+            (None, Some(key.to_string()), locked)
+        }
+        Err(e) => {
+            let current_theme = state.current_theme.lock().unwrap();
+            let content = format!(
+                "<h1>Shortcut Chain Error</h1><p style=\"margin: 10px;\">{}</p>",
+                encode_minimal(&e.describe())
+            );
+            return Some(
+                HttpResponse::build(StatusCode::LOOP_DETECTED)
+                    .content_type("text/html; charset=utf-8")
+                    .body(render_base_page("Shortcut Chain Error", &content, &current_theme)),
+            );
+        }
+    };
+
+    let entry = alias.as_deref().and_then(|a| {
+        find_active_shortcut(&collections, a, now)
+            .or_else(|| find_shortcut(&collections, a))
+            .map(|(c, s)| (a, c, s))
+    });
+
+    // Hidden shortcuts aren't listed anywhere without the passphrase, so neither are their details
+    let hidden = entry.is_some_and(|(_, c, _)| c.hidden);
+    if (locked || hidden) && !hidden::is_unlocked(req, state) {
+        return Some(hidden::unlock_redirect(req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/")));
+    }
+
+    let current_theme = state.current_theme.lock().unwrap();
+    let content = render_preview(state, target, destination.as_deref(), entry, now);
+    Some(
+        HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(
+                render_base_page("Shortcut Preview", &content, &current_theme)
+                    .replace("</body>", &format!("{}</body>", render_edit_shortcut_modal())),
+            ),
+    )
+}
+
+fn render_preview(
+    state: &AppState,
+    target: &str,
+    destination: Option<&str>,
+    entry: Option<(&str, &ShortcutCollection, &Shortcut)>,
+    now: u64,
+) -> String {
+    let destination_html = match destination {
+        Some(url) => format!(
+            "<p class=\"preview-destination\">Goes to <a href=\"{0}\" rel=\"noreferrer\">{0}</a></p>",
+            encode_minimal(url)
+        ),
+        None => "<p class=\"preview-destination\">Doesn't redirect right now; the shortcut isn't active.</p>".to_string(),
+    };
+
+    let qr_html = match destination.and_then(|url| QrCode::encode(url.as_bytes())) {
+        Some(code) => format!("<div class=\"preview-qr\">{}<small>Scan to open on a phone</small></div>", code.to_svg()),
+        None if destination.is_some() => "<div class=\"preview-qr\"><small>Too long for a QR code</small></div>".to_string(),
+        None => String::new(),
+    };
+
+    let Some((alias, collection, shortcut)) = entry else {
+        // Only rules match without an alias
+        return format!(
+            r#"
+    <h1>go/{}</h1>
+    {}
+    <div class="preview">
+        <table class="grid preview-details">
+            <tr><th>Matched</th><td>A rule shortcut (<a href="/settings">settings</a>)</td></tr>
+        </table>
+        {}
+    </div>
+    "#,
+            encode_minimal(target),
+            destination_html,
+            qr_html
+        );
+    };
+
+    let mut rows = Vec::new();
+    let mut row = |label: &str, value: String| {
+        if !value.is_empty() {
+            rows.push(format!("<tr><th>{}</th><td>{}</td></tr>", label, value));
+        }
+    };
+
+    row("Shortcut", format!("<a href=\"/{0}\">{0}</a>", encode_minimal(alias)));
+    if destination != Some(shortcut.url.as_str()) {
+        row("Stored URL", format!("<span class=\"url\">{}</span>", encode_minimal(&shortcut.url)));
+    }
+    row("Description", encode_minimal(&shortcut.description));
+    let source = match collection.sources.get(alias) {
+        Some(source) => format!("{}, read-only", source),
+        None => collection.file.clone(),
+    };
+    row(
+        "Collection",
+        format!(
+            "{} <small>({}{})</small>",
+            encode_minimal(&collection.name),
+            encode_minimal(&source),
+            if collection.hidden { ", hidden" } else { "" }
+        ),
+    );
+    row(
+        "Tags",
+        shortcut
+            .tags
+            .iter()
+            .map(|t| format!("<span class=\"tag\">{}</span>", encode_minimal(t)))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    row("Owner", encode_minimal(&shortcut.owner));
+    row("Notes", encode_minimal(&shortcut.notes));
+    if shortcut.active_from != 0 {
+        row("Active from", format!("{} UTC", format_datetime(shortcut.active_from)));
+    }
+    if shortcut.expires_at != 0 {
+        let label = if shortcut.is_expired(now) { "Expired" } else { "Expires" };
+        row(label, format!("{} UTC", format_datetime(shortcut.expires_at)));
+    }
+    if shortcut.locked {
+        row("Locked", "Redirects only while the hidden shortcuts are unlocked".to_string());
+    }
+    if shortcut.created_at != 0 {
+        row("Created", format_date(shortcut.created_at));
+    }
+    if shortcut.updated_at != 0 {
+        row("Updated", format_date(shortcut.updated_at));
+    }
+
+    // Usage, counted by `go` redirects (this page doesn't count as a visit)
+    let visits = match state.stats.lock().unwrap().get(alias).filter(|s| s.hits > 0) {
+        Some(stats) => {
+            let since = format_date(now.saturating_sub((stats::HISTOGRAM_DAYS - 1) * SECONDS_IN_DAY));
+            let recent: u64 = stats.daily.range(since..).map(|(_, count)| count).sum();
+            format!(
+                "{} in total, {} in the last {} days, last on {} UTC",
+                stats.hits,
+                recent,
+                stats::HISTOGRAM_DAYS,
+                format_datetime(stats.last_used)
+            )
+        }
+        None => "Never used".to_string(),
+    };
+    row("Visits", visits);
+    if let Some(health) = state.health.lock().unwrap().get(&shortcut.url) {
+        row(
+            "Link check",
+            format!("{} at {} UTC", encode_minimal(&health.summary()), format_datetime(health.checked_at)),
+        );
+    }
+
+    // Hidden shortcuts are changed from /hidden, fragment files in their repository
+    let open_button = destination
+        .map(|url| format!("<a href=\"{}\" rel=\"noreferrer\"><button type=\"button\">Open</button></a>", encode_minimal(url)))
+        .unwrap_or_default();
+    let actions = if collection.directory {
+        String::new()
+    } else if collection.hidden {
+        "<a href=\"/hidden\"><button type=\"button\">Manage on /hidden</button></a>".to_string()
+    } else {
+        format!(
+            r#"<button type="button" class="edit-shortcut-btn preview-edit" {data}>Edit</button>
+        <form action="/delete_shortcut" method="POST" style="display:inline;" onsubmit="return confirm('Are you sure you want to delete shortcut: {key}?');">
+            <input type="hidden" name="key" value="{key}">
+            <button type="submit" class="delete-button">Delete</button>
+        </form>"#,
+            data = edit_shortcut_data(alias, shortcut),
+            key = encode_minimal(alias)
+        )
+    };
+
+    format!(
+        r#"
+    <h1>go/{target}</h1>
+    {destination}
+    <div class="preview">
+        <table class="grid preview-details">
+            {rows}
+        </table>
+        {qr}
+    </div>
+    <div class="preview-actions">
+        {open}
+        {actions}
+    </div>
+    "#,
+        target = encode_minimal(target),
+        destination = destination_html,
+        rows = rows.join("\n            "),
+        qr = qr_html,
+        open = open_button,
+        actions = actions
+    )
+}
@@ -0,0 +1,519 @@
+// Minimal QR code generator for the shortcut preview page, so a link can be opened on a phone
+// without sending it to an outside service. Encodes bytes (mode 4) at error correction level M
+// and renders the symbol as inline SVG.
+//
+// Follows ISO/IEC 18004: pick the smallest version the data fits in, add Reed-Solomon error
+// correction per block, interleave the blocks, place them around the function patterns and
+// keep the mask with the lowest penalty score.
+
+// Error correction codewords per block and number of blocks at level M, by version (1-40)
+const ECC_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+const BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29,
+    31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+
+// Format information bits for level M
+const ECL_M: u32 = 0;
+
+// Light modules around the symbol that scanners need to find it
+const QUIET_ZONE: usize = 4;
+
+pub struct QrCode {
+    size: usize,
+    // Row-major; true is a dark module
+    modules: Vec<bool>,
+    // Finder, timing, alignment, format and version modules, which masks leave alone
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    // None if the data is too long for the largest version
+    pub fn encode(data: &[u8]) -> Option<QrCode> {
+        let version = (1..=40).find(|&v| 4 + count_bits(v) + data.len() * 8 <= data_codewords(v) * 8)?;
+        let codewords = add_error_correction(&data_bits(data, version), version);
+
+        let size = version * 4 + 17;
+        let mut qr = QrCode { size, modules: vec![false; size * size], function: vec![false; size * size] };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&codewords);
+
+        // Keep the mask that is easiest to scan (lowest penalty)
+        let mut best = (u32::MAX, 0);
+        for mask in 0..8 {
+            qr.apply_mask(mask);
+            qr.draw_format_bits(mask);
+            let penalty = qr.penalty();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            qr.apply_mask(mask); // XOR again to undo
+        }
+        qr.apply_mask(best.1);
+        qr.draw_format_bits(best.1);
+        Some(qr)
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        // Timing patterns
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        // Finder patterns with their separators, in three corners
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+
+        // Alignment patterns, except where they would overlap the finders
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &cx) in positions.iter().enumerate() {
+            for (j, &cy) in positions.iter().enumerate() {
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function((cx as i32 + dx) as usize, (cy as i32 + dy) as usize, dist != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas (filled in per mask) and add the version blocks
+        self.draw_format_bits(0);
+        if version >= 7 {
+            let mut rem = version as u32;
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+            }
+            let bits = ((version as u32) << 12) | rem;
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let size = self.size;
+        let data = (ECL_M << 3) | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+
+        // Next to the top left finder
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // The copy split between the other two finders, plus the always dark module
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    // Places the codewords in the two-module wide zigzag from the bottom right corner
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total_bits = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            // The vertical timing pattern column is skipped
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                let y = if upward { size - 1 - vert } else { vert };
+                for x in [right, right - 1] {
+                    if !self.function[y * size + x] && i < total_bits {
+                        self.modules[y * size + x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                if invert && !self.function[index] {
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    // Penalty score from the standard: long runs, 2x2 blocks, finder-like patterns and an
+    // uneven balance of dark and light modules all make a symbol harder to read
+    fn penalty(&self) -> u32 {
+        let size = self.size;
+        let mut penalty = 0;
+        let row = |y: usize| (0..size).map(|x| self.get(x, y)).collect::<Vec<_>>();
+        let column = |x: usize| (0..size).map(|y| self.get(x, y)).collect::<Vec<_>>();
+        let lines = (0..size).map(row).chain((0..size).map(column));
+
+        const FINDER: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+        let finder_reversed: Vec<bool> = FINDER.iter().rev().copied().collect();
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += 3 + (run - 5);
+                }
+                run = 1;
+            }
+            for window in line.windows(FINDER.len()) {
+                if window == FINDER || window == finder_reversed.as_slice() {
+                    penalty += 40;
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.get(x, y);
+                if dark == self.get(x + 1, y) && dark == self.get(x, y + 1) && dark == self.get(x + 1, y + 1) {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let dark = self.modules.iter().filter(|d| **d).count() as i64;
+        let total = (size * size) as i64;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        penalty + k.max(0) as u32 * 10
+    }
+
+    // The symbol as an SVG image, dark modules on white whatever the page theme is
+    pub fn to_svg(&self) -> String {
+        let dimension = self.size + QUIET_ZONE * 2;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.get(x, y) {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+                }
+            }
+        }
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {0} {0}" shape-rendering="crispEdges" role="img" aria-label="QR code"><rect width="100%" height="100%" fill="#fff"/><path d="{1}" fill="#000"/></svg>"##,
+            dimension, path
+        )
+    }
+}
+
+// Length field size of byte mode
+fn count_bits(version: usize) -> usize {
+    if version <= 9 { 8 } else { 16 }
+}
+
+// Modules left for data and error correction once the function patterns are placed
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8 - ECC_PER_BLOCK[version] * BLOCKS[version]
+}
+
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = if version == 32 { 26 } else { (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2 };
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| version * 4 + 10 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+// Mode indicator, length, the data, a terminator and padding up to the version's capacity
+fn data_bits(data: &[u8], version: usize) -> Vec<u8> {
+    let mut bits: Vec<bool> = Vec::new();
+    let mut push = |value: usize, len: usize| {
+        for i in (0..len).rev() {
+            bits.push((value >> i) & 1 == 1);
+        }
+    };
+    push(0b0100, 4);
+    push(data.len(), count_bits(version));
+    for &b in data {
+        push(b as usize, 8);
+    }
+
+    let capacity = data_codewords(version) * 8;
+    let terminator = (capacity - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    bits.extend(std::iter::repeat_n(false, (8 - bits.len() % 8) % 8));
+
+    let mut bytes: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |acc, &b| (acc << 1) | b as u8))
+        .collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if bytes.len() >= data_codewords(version) {
+            break;
+        }
+        bytes.push(pad);
+    }
+    bytes
+}
+
+// Splits the data into blocks, appends each block's Reed-Solomon codewords and interleaves them
+fn add_error_correction(data: &[u8], version: usize) -> Vec<u8> {
+    let blocks = BLOCKS[version];
+    let ecc_len = ECC_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw_codewords % blocks;
+    let short_len = raw_codewords / blocks;
+    let divisor = rs_divisor(ecc_len);
+
+    // Short blocks get a placeholder byte so every block has the same length
+    let mut split: Vec<Vec<u8>> = Vec::with_capacity(blocks);
+    let mut offset = 0;
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let mut block = data[offset..offset + len].to_vec();
+        offset += len;
+        let ecc = rs_remainder(&block, &divisor);
+        if i < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        split.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..=short_len {
+        for (j, block) in split.iter().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+// Generator polynomial of the given degree, highest coefficient dropped
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = rs_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = rs_multiply(root, 0x02);
+    }
+    result
+}
+
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= rs_multiply(d, factor);
+        }
+    }
+    result
+}
+
+// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn rs_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Format information for level M and masks 0-7, from the table in ISO/IEC 18004
+    const FORMAT_M: [u32; 8] = [0x5412, 0x5125, 0x5E7C, 0x5B4B, 0x45F9, 0x40CE, 0x4F97, 0x4AA0];
+
+    fn read_format(qr: &QrCode) -> (u32, u32) {
+        let size = qr.size;
+        let mut first = [(8, 0), (8, 1), (8, 2), (8, 3), (8, 4), (8, 5), (8, 7), (8, 8), (7, 8)].to_vec();
+        first.extend((9..15).map(|i| (14 - i, 8)));
+        let mut second: Vec<(usize, usize)> = (0..8).map(|i| (size - 1 - i, 8)).collect();
+        second.extend((8..15).map(|i| (8, size - 15 + i)));
+
+        let read = |positions: &[(usize, usize)]| {
+            positions.iter().enumerate().fold(0, |acc, (i, &(x, y))| acc | (u32::from(qr.get(x, y)) << i))
+        };
+        (read(&first), read(&second))
+    }
+
+    #[test]
+    fn reed_solomon_matches_the_spec_examples() {
+        // "01234567" as 1-M (ISO/IEC 18004 annex I)
+        let data = [16, 32, 12, 86, 97, 128, 236, 17, 236, 17, 236, 17, 236, 17, 236, 17];
+        assert_eq!(rs_remainder(&data, &rs_divisor(10)), [165, 36, 212, 193, 237, 54, 199, 135, 44, 85]);
+
+        // "HELLO WORLD" as 1-M
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17];
+        let codewords = add_error_correction(&data, 1);
+        assert_eq!(codewords[..16], data);
+        assert_eq!(codewords[16..], [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn byte_mode_data_is_padded_to_capacity() {
+        assert_eq!(
+            data_bits(b"hello", 1),
+            [0x40, 0x56, 0x86, 0x56, 0xC6, 0xC6, 0xF0, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC]
+        );
+        // From version 10 the length takes 16 bits
+        assert_eq!(data_bits(b"A", 10)[..4], [0x40, 0x00, 0x14, 0x10]);
+    }
+
+    #[test]
+    fn blocks_are_interleaved_short_ones_first() {
+        // 10-M: four blocks of 43 data codewords and one of 44, 26 ECC codewords each
+        let data: Vec<u8> = (0..216).map(|i| i as u8).collect();
+        let codewords = add_error_correction(&data, 10);
+        assert_eq!(codewords.len(), 346);
+        assert_eq!(codewords[..5], [0, 43, 86, 129, 172]);
+        // Only the long block has a 44th data codeword
+        assert_eq!(codewords[215], 215);
+        let first_ecc = rs_remainder(&data[..43], &rs_divisor(26));
+        assert_eq!(codewords[216], first_ecc[0]);
+        assert_eq!(codewords[216 + 5 * 25], first_ecc[25]);
+    }
+
+    #[test]
+    fn picks_the_smallest_version() {
+        // Byte mode capacities at level M
+        for (len, version) in [(1, 1), (14, 1), (15, 2), (26, 2), (27, 3), (213, 10), (214, 11), (2331, 40)] {
+            let qr = QrCode::encode(&vec![b'a'; len]).unwrap();
+            assert_eq!(qr.size, version * 4 + 17, "{} bytes", len);
+        }
+        assert!(QrCode::encode(&[b'a'; 2332]).is_none());
+    }
+
+    #[test]
+    fn draws_function_patterns_and_format_information() {
+        for url in ["https://go.example/gh", "https://github.com/rust-lang/rust/issues?q=is%3Aopen+label%3AE-easy"] {
+            let qr = QrCode::encode(url.as_bytes()).unwrap();
+            let size = qr.size;
+
+            // Finder centres and corners, separators, timing and the dark module
+            for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4), (0, 0), (size - 1, 6), (6, size - 1)] {
+                assert!(qr.get(x, y), "({}, {})", x, y);
+            }
+            for (x, y) in [(7, 7), (size - 8, 7), (7, size - 8), (1, 1)] {
+                assert!(!qr.get(x, y), "({}, {})", x, y);
+            }
+            assert!((8..size - 8).all(|i| qr.get(i, 6) == (i % 2 == 0) && qr.get(6, i) == (i % 2 == 0)));
+            assert!(qr.get(8, size - 8));
+
+            // Both copies of the format information, for one of the level M masks
+            let (first, second) = read_format(&qr);
+            assert_eq!(first, second);
+            assert!(FORMAT_M.contains(&first), "{:015b}", first);
+        }
+    }
+
+    #[test]
+    fn draws_version_information() {
+        // 7 and 40 from the version information table in ISO/IEC 18004
+        for (len, version, expected) in [(122, 7, 0x07C94), (2331, 40, 0x28C69)] {
+            let qr = QrCode::encode(&vec![b'a'; len]).unwrap();
+            assert_eq!(qr.size, version * 4 + 17);
+            let size = qr.size;
+            let top_right = (0..18).fold(0, |acc, i| acc | (u32::from(qr.get(size - 11 + i % 3, i / 3)) << i));
+            let bottom_left = (0..18).fold(0, |acc, i| acc | (u32::from(qr.get(i / 3, size - 11 + i % 3)) << i));
+            assert_eq!(top_right, expected);
+            assert_eq!(bottom_left, expected);
+        }
+    }
+
+    #[test]
+    fn renders_every_dark_module() {
+        let qr = QrCode::encode(b"https://go.example/").unwrap();
+        let svg = qr.to_svg();
+        let dark = qr.modules.iter().filter(|&&m| m).count();
+        assert_eq!(svg.matches("h1v1h-1z").count(), dark);
+        assert!(svg.contains(&format!("viewBox=\"0 0 {0} {0}\"", qr.size + 2 * QUIET_ZONE)));
+    }
+}
@@ -3,10 +3,9 @@ use std::{fs, io, process::Command, collections::HashMap};
 use serde::{Deserialize, Serialize};
 use crate::app_state::{AppState, Theme};
 use crate::base_page::render_base_page;
+use crate::config::files;
 use htmlescape::encode_minimal;
 
-const REQUESTS_FILE: &str = "saved_requests.json";
-
 #[derive(Serialize, Deserialize, Clone)]
 struct SavedRequest {
     name: String,
@@ -51,7 +50,7 @@ struct ProxyRequest {
 }
 
 fn load_requests() -> Vec<SavedRequest> {
-    fs::read_to_string(REQUESTS_FILE)
+    fs::read_to_string(&files().requests)
         .ok()
         .and_then(|data| serde_json::from_str(&data).ok())
         .unwrap_or_default()
@@ -59,7 +58,7 @@ fn load_requests() -> Vec<SavedRequest> {
 
 fn save_requests_to_file(requests: &[SavedRequest]) -> io::Result<()> {
     let data = serde_json::to_string_pretty(requests)?;
-    fs::write(REQUESTS_FILE, data)
+    fs::write(&files().requests, data)
 }
 
 // --- Handlers ---
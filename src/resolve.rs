@@ -0,0 +1,669 @@
+// Helpers used by the catch-all `go` route to turn a requested path into a destination URL.
+// Kept free of request handling so the same logic can be reused by other entry points.
+
+use crate::app_state::{GoSettings, ShortcutCollection};
+use crate::elements::shortcut::{find_active_shortcut, find_shortcut};
+use crate::rules::{match_rule, CompiledRule};
+use crate::variables::{self, VariableContext};
+
+/// Targets starting with this prefix name another alias (e.g. `go:gh/org/repo`) and are
+/// resolved internally instead of sending the browser through a second redirect.
+pub const CHAIN_PREFIX: &str = "go:";
+
+/// How many `go:` hops a single request may follow.
+pub const MAX_CHAIN_DEPTH: usize = 8;
+
+/// A successfully resolved request.
+pub struct Resolved {
+    /// Destination, before the request's query and fragment are merged in.
+    pub url: String,
+    /// The alias the request itself matched (not an alias reached through chaining), if any.
+    /// Rule matches have no alias.
+    pub alias: Option<String>,
+    /// An alias on the way (including chained ones) is locked and needs the hidden-shortcuts
+    /// unlock session before redirecting.
+    pub locked: bool,
+}
+
+pub enum ResolveError {
+    NotFound,
+    /// The chain came back to a path it had already resolved.
+    Cycle(Vec<String>),
+    /// The chain was longer than `MAX_CHAIN_DEPTH`.
+    TooDeep(Vec<String>),
+}
+
+impl ResolveError {
+    pub fn describe(&self) -> String {
+        match self {
+            ResolveError::NotFound => "No shortcut matches this path.".to_string(),
+            ResolveError::Cycle(chain) => format!("Alias chain forms a cycle: {}", chain.join(" -> ")),
+            ResolveError::TooDeep(chain) => format!(
+                "Alias chain is longer than {} steps: {}",
+                MAX_CHAIN_DEPTH,
+                chain.join(" -> ")
+            ),
+        }
+    }
+}
+
+/// What an alias lookup found.
+enum Lookup {
+    /// The URL of a shortcut inside its active window.
+    Active(String),
+    /// The alias only exists outside its active window; redirect to the configured fallback.
+    Inactive(String),
+}
+
+/// Everything needed to resolve a path: shortcut collections (in resolution order), rule
+/// shortcuts, settings and the values of `{today}`-style variables.
+pub struct Resolver<'a> {
+    pub collections: &'a [ShortcutCollection],
+    pub rules: &'a [CompiledRule],
+    pub settings: &'a GoSettings,
+    pub variables: VariableContext,
+}
+
+impl Resolver<'_> {
+    /// Resolves a request path (without leading `/`), following `go:` chains.
+    /// `query` holds the request's query parameters; templates remove the ones they use, and
+    /// parameters from a chained target are added unless the request already has them.
+    pub fn resolve(&self, path: &str, query: &mut Vec<(String, String)>) -> Result<Resolved, ResolveError> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut current = path.to_string();
+        let mut first_alias: Option<Option<String>> = None;
+        let mut locked = false;
+
+        loop {
+            if chain.contains(&current) {
+                chain.push(current);
+                return Err(ResolveError::Cycle(chain));
+            }
+            chain.push(current.clone());
+            if chain.len() > MAX_CHAIN_DEPTH + 1 {
+                return Err(ResolveError::TooDeep(chain));
+            }
+
+            let (url, alias) = self.resolve_once(&current, query).ok_or(ResolveError::NotFound)?;
+            locked |= alias
+                .as_deref()
+                .and_then(|a| find_shortcut(self.collections, a))
+                .is_some_and(|(_, s)| s.locked);
+            let alias = first_alias.get_or_insert(alias).clone();
+
+            let Some(next) = url.strip_prefix(CHAIN_PREFIX) else {
+                return Ok(Resolved { url, alias, locked });
+            };
+
+            // Follow the chain; the target may carry its own query (`go:search?q=x`)
+            let (next_path, next_query) = next.split_once('?').unwrap_or((next, ""));
+            if let Ok(pairs) = parse_query(next_query) {
+                for (k, v) in pairs {
+                    if !query.iter().any(|(qk, _)| *qk == k) {
+                        query.push((k, v));
+                    }
+                }
+            }
+            current = next_path.trim_start_matches('/').to_string();
+        }
+    }
+
+    /// Resolves a whole request (`path?query`, leading `/` optional) to the final URL, with
+    /// the request's remaining query merged in.
+    pub fn resolve_request(&self, request: &str) -> Result<Resolved, ResolveError> {
+        let request = request.trim_start_matches('/');
+        let (path, query) = request.split_once('?').unwrap_or((request, ""));
+        let mut params = parse_query(query).unwrap_or_default();
+        let resolved = self.resolve(path, &mut params)?;
+        Ok(Resolved { url: merge_query(&resolved.url, &params, None), ..resolved })
+    }
+
+    /// One resolution step: exact match, then rules, then smart append.
+    /// Returns the destination and the alias that matched (None for rules).
+    fn resolve_once(&self, path: &str, query: &mut Vec<(String, String)>) -> Option<(String, Option<String>)> {
+        // Expired and not yet active shortcuts are skipped, so a later collection, a rule or the
+        // 404 page gets the path; with `inactive_fallback_url` set they go there instead.
+        let now = self.variables.now;
+        let fallback = &self.settings.inactive_fallback_url;
+        let find_url = |key: &str| match find_active_shortcut(self.collections, key, now) {
+            Some((_, s)) => Some(Lookup::Active(s.url.clone())),
+            None if !fallback.is_empty() && find_shortcut(self.collections, key).is_some() => {
+                Some(Lookup::Inactive(fallback.clone()))
+            }
+            None => None,
+        };
+
+        // Templated shortcuts fill their placeholders from the variables, the remaining segments
+        // and the query, falling back to the configured default when an argument is missing.
+        let expand = |template: &str, segments: &[&str], query: &mut Vec<(String, String)>| -> String {
+            expand_template(template, segments, query, |name| self.variable(name))
+                .unwrap_or_else(|| template_fallback(template, &self.settings.template_fallback_url))
+        };
+
+        // 1. Exact Match: Check if the full path is a defined shortcut
+        match find_url(path) {
+            Some(Lookup::Active(url)) => return Some((expand(&url, &[], query), Some(path.to_string()))),
+            Some(Lookup::Inactive(url)) => return Some((url, Some(path.to_string()))),
+            None => {}
+        }
+
+        // 2. Rules: regex and prefix rules, in order (e.g. "pr/42" -> ".../pull/42")
+        if let Some((_, url)) = match_rule(self.rules, path) {
+            return Some((self.expand_variables(&url), None));
+        }
+
+        // 3. Smart Append: Check if the first segment is a shortcut (e.g. "youtube/omegagiven")
+        // This splits "youtube/omegagiven" into "youtube" and "omegagiven"
+        let (alias, remainder) = path.split_once('/')?;
+        let base_url = match find_url(alias)? {
+            Lookup::Active(url) => url,
+            Lookup::Inactive(url) => return Some((url, Some(alias.to_string()))),
+        };
+        let new_url = if self.takes_arguments(&base_url) {
+            // Templates consume the remainder as positional arguments instead of appending it.
+            let segments: Vec<&str> = remainder.split('/').collect();
+            expand(&base_url, &segments, query)
+        } else {
+            // If the base URL ends with '/', just append. Otherwise add '/' then append.
+            let base_url = self.expand_variables(&base_url);
+            if base_url.ends_with('/') {
+                format!("{}{}", base_url, remainder)
+            } else {
+                format!("{}/{}", base_url, remainder)
+            }
+        };
+        Some((new_url, Some(alias.to_string())))
+    }
+
+    /// The current value of a variable (`{today}`, `{user}`, ...), encoded for the URL. `/` is
+    /// kept so a configured variable can hold a path. None for template arguments and for
+    /// variables without a value right now.
+    fn variable(&self, name: &str) -> Option<String> {
+        variables::lookup(name, &self.variables).map(|value| encode_capture(&value))
+    }
+
+    /// True if placeholders are left for template arguments once the variables are filled in.
+    fn takes_arguments(&self, url: &str) -> bool {
+        placeholders(url).iter().any(|p| self.variable(p.name).is_none())
+    }
+
+    /// Replaces variables with their current values. Placeholders that aren't variables, or
+    /// have no value right now, are left as they are.
+    pub fn expand_variables(&self, url: &str) -> String {
+        let mut result = String::with_capacity(url.len());
+        let mut last = 0;
+        for p in placeholders(url) {
+            if let Some(value) = self.variable(p.name) {
+                result.push_str(&url[last..p.start]);
+                result.push_str(&value);
+                last = p.end;
+            }
+        }
+        result.push_str(&url[last..]);
+        result
+    }
+}
+
+/// Decodes an `a=1&b=2` query string into pairs.
+pub fn parse_query(query: &str) -> Result<Vec<(String, String)>, actix_web::error::QueryPayloadError> {
+    actix_web::web::Query::<Vec<(String, String)>>::from_query(query).map(|q| q.into_inner())
+}
+
+/// A placeholder found inside a templated shortcut URL, e.g. `{0}` or `{org}`.
+struct Placeholder<'a> {
+    start: usize,
+    end: usize,
+    name: &'a str,
+}
+
+/// Finds every `{name}` placeholder in a URL. Names are limited to ASCII letters,
+/// digits and underscores so that other braces in a URL are left alone.
+fn placeholders(url: &str) -> Vec<Placeholder<'_>> {
+    let mut found = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(open) = url[search_from..].find('{').map(|i| i + search_from) {
+        let Some(close) = url[open..].find('}').map(|i| i + open) else {
+            break;
+        };
+        let name = &url[open + 1..close];
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            found.push(Placeholder { start: open, end: close + 1, name });
+            search_from = close + 1;
+        } else {
+            search_from = open + 1;
+        }
+    }
+    found
+}
+
+/// Fills in the placeholders of a templated shortcut URL.
+///
+/// Placeholders `variable` has a value for (`{today}`) take that value, which must already be
+/// encoded. Positional placeholders (`{0}`, `{1}`, ...) take the path segments after the alias.
+/// Named placeholders (`{org}`) take the query parameter with the same name; a numeric
+/// placeholder without a matching segment also falls back to a query parameter.
+/// Query parameters used this way are removed from `query` so they are not appended twice.
+/// Everything is filled in one pass, so a value that itself looks like `{0}` is not expanded.
+///
+/// Returns `None` if any placeholder has no value.
+pub fn expand_template(
+    template: &str,
+    segments: &[&str],
+    query: &mut Vec<(String, String)>,
+    variable: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let mut result = String::with_capacity(template.len());
+    let mut last = 0;
+    let mut consumed: Vec<&str> = Vec::new();
+
+    for p in placeholders(template) {
+        if let Some(value) = variable(p.name) {
+            result.push_str(&template[last..p.start]);
+            result.push_str(&value);
+            last = p.end;
+            continue;
+        }
+
+        let positional = p
+            .name
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| segments.get(i))
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+
+        let value = match positional {
+            Some(v) => v,
+            None => {
+                let (_, v) = query.iter().find(|(k, v)| k == p.name && !v.is_empty())?;
+                consumed.push(p.name);
+                v.clone()
+            }
+        };
+
+        result.push_str(&template[last..p.start]);
+        result.push_str(&encode_component(&value));
+        last = p.end;
+    }
+    result.push_str(&template[last..]);
+
+    query.retain(|(k, _)| !consumed.contains(&k.as_str()));
+    Some(result)
+}
+
+/// Where a templated shortcut goes when its arguments are missing. Uses the configured
+/// fallback if there is one, otherwise the part of the template before the first placeholder
+/// (cut back to the last `/`). When the placeholder is in the host itself
+/// (`https://{0}.example.com`) there is no such page, so it goes to the home page.
+pub fn template_fallback(template: &str, configured: &str) -> String {
+    let configured = configured.trim();
+    if !configured.is_empty() {
+        return configured.to_string();
+    }
+
+    let Some(first) = placeholders(template).first().map(|p| p.start) else {
+        return template.to_string();
+    };
+    let prefix = &template[..first];
+    // Only cut at a slash that belongs to the path, after a complete host
+    let path_start = match prefix.find("://") {
+        Some(i) => i + 3,
+        None => 0,
+    };
+    match prefix[path_start..].rfind('/') {
+        Some(i) if path_start == 0 || i > 0 => prefix[..=path_start + i].to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+/// Carries the original request's query string and fragment over to the destination URL.
+///
+/// Precedence rule when both sides have a query: parameters from the request win. A request
+/// parameter replaces every target parameter with the same key (at the position of the first
+/// one); request parameters the target doesn't have are appended in request order. A request
+/// fragment likewise replaces the target's fragment.
+pub fn merge_query(target: &str, query: &[(String, String)], fragment: Option<&str>) -> String {
+    let (rest, target_fragment) = match target.split_once('#') {
+        Some((r, f)) => (r, Some(f)),
+        None => (target, None),
+    };
+    let (base, target_query) = match rest.split_once('?') {
+        Some((b, q)) => (b, q),
+        None => (rest, ""),
+    };
+
+    // Keep the target's own pairs verbatim (including bare flags like `?raw`);
+    // only the request's pairs need encoding.
+    let mut pairs: Vec<(String, Option<String>)> = target_query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.split_once('=') {
+            Some((k, v)) => (k.to_string(), Some(v.to_string())),
+            None => (p.to_string(), None),
+        })
+        .collect();
+
+    let mut seen: Vec<String> = Vec::new();
+    for (k, v) in query {
+        let key = encode_component(k);
+        let value = Some(encode_component(v));
+        if seen.contains(&key) {
+            // Repeated request keys (e.g. `tag=a&tag=b`) are all kept.
+            pairs.push((key, value));
+            continue;
+        }
+        seen.push(key.clone());
+        match pairs.iter().position(|(pk, _)| *pk == key) {
+            Some(pos) => {
+                pairs[pos].1 = value;
+                let mut index = 0;
+                pairs.retain(|(pk, _)| {
+                    let keep = index <= pos || *pk != key;
+                    index += 1;
+                    keep
+                });
+            }
+            None => pairs.push((key, value)),
+        }
+    }
+
+    let mut url = base.to_string();
+    if !pairs.is_empty() {
+        let joined = pairs
+            .iter()
+            .map(|(k, v)| match v {
+                Some(v) => format!("{}={}", k, v),
+                None => k.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        url.push('?');
+        url.push_str(&joined);
+    }
+    if let Some(f) = fragment.filter(|f| !f.is_empty()).or(target_fragment) {
+        url.push('#');
+        url.push_str(f);
+    }
+    url
+}
+
+// How many "did you mean" candidates to show on the 404 page.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Edit distance allowing insertions, deletions, substitutions and adjacent transpositions
+/// (optimal string alignment), so `gtihub` is one edit away from `github`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
+/// A "did you mean" candidate for a missed alias.
+pub struct Suggestion {
+    pub key: String,
+    pub distance: usize,
+}
+
+/// Ranks aliases against a missed key by edit distance, then by shared prefix length.
+/// Keys are candidates if they are within roughly a third of the key's length in edits,
+/// or if they start with the missed key (e.g. `go/jir` -> `jira`).
+pub fn suggest<'a>(missed: &str, keys: impl Iterator<Item = &'a String>) -> Vec<Suggestion> {
+    let missed_lower = missed.to_lowercase();
+    let max_distance = (missed.chars().count() / 3).max(1);
+
+    let mut ranked: Vec<(usize, usize, &String)> = keys
+        .filter_map(|k| {
+            let key_lower = k.to_lowercase();
+            let distance = edit_distance(&missed_lower, &key_lower);
+            let prefix = common_prefix_len(&missed_lower, &key_lower);
+            let is_prefix = missed_lower.chars().count() >= 2 && key_lower.starts_with(&missed_lower);
+            (distance <= max_distance || is_prefix).then_some((distance, prefix, k))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(distance, _, k)| Suggestion { key: k.clone(), distance })
+        .collect()
+}
+
+/// The single suggestion that is safe to redirect to without asking: only one candidate is
+/// within one edit of the missed key, and the key is long enough for that to be meaningful.
+pub fn auto_redirect_target<'a>(missed: &str, suggestions: &'a [Suggestion]) -> Option<&'a Suggestion> {
+    if missed.chars().count() < 3 {
+        return None;
+    }
+    let mut close = suggestions.iter().filter(|s| s.distance <= 1);
+    match (close.next(), close.next()) {
+        (Some(only), None) => Some(only),
+        _ => None,
+    }
+}
+
+/// Host part of a URL, without a leading `www.`.
+pub fn host_of(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let host = without_scheme.split(['/', '?', '#']).next().unwrap_or("");
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+/// Percent-encodes a value so it can be placed inside a URL path or query component.
+pub fn encode_component(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Like `encode_component`, but keeps `/` so a captured sub-path stays a path.
+pub fn encode_capture(value: &str) -> String {
+    value.split('/').map(encode_component).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::{CollectionConfig, Shortcut};
+
+    fn collection(pairs: &[(&str, &str)]) -> ShortcutCollection {
+        let config = CollectionConfig {
+            name: "Shortcuts".to_string(),
+            file: "shortcuts.json".to_string(),
+            hidden: false,
+            priority: None,
+            directory: false,
+        };
+        let shortcuts = pairs
+            .iter()
+            .map(|(k, url)| (k.to_string(), Shortcut { url: url.to_string(), ..Default::default() }))
+            .collect();
+        ShortcutCollection::new(&config, 0, shortcuts)
+    }
+
+    fn resolve(collections: &[ShortcutCollection], request: &str) -> Result<String, ResolveError> {
+        let settings = GoSettings::default();
+        let resolver = Resolver { collections, rules: &[], settings: &settings, variables: VariableContext::default() };
+        resolver.resolve_request(request).map(|r| r.url)
+    }
+
+    fn query(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn expands_positional_and_named_placeholders() {
+        let mut q = query(&[("org", "rust-lang"), ("repo", "rust"), ("tab", "issues")]);
+        let url = expand_template("https://github.com/{org}/{repo}/pull/{0}", &["42"], &mut q, |_| None);
+        assert_eq!(url.as_deref(), Some("https://github.com/rust-lang/rust/pull/42"));
+        // Used parameters are consumed, the rest is left for the query string
+        assert_eq!(q, query(&[("tab", "issues")]));
+
+        // A numeric placeholder without a segment comes from the query
+        let mut q = query(&[("0", "123")]);
+        let url = expand_template("https://jira.example.com/browse/PROJ-{0}", &[], &mut q, |_| None);
+        assert_eq!(url.as_deref(), Some("https://jira.example.com/browse/PROJ-123"));
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn expand_template_encodes_values_and_reports_missing_ones() {
+        let url = expand_template("https://example.com/?q={0}", &["a b&c/d"], &mut Vec::new(), |_| None);
+        assert_eq!(url.as_deref(), Some("https://example.com/?q=a%20b%26c%2Fd"));
+
+        assert!(expand_template("https://example.com/{0}/{1}", &["a"], &mut Vec::new(), |_| None).is_none());
+        // Empty segments and parameters count as missing
+        let mut q = query(&[("org", "")]);
+        assert!(expand_template("https://example.com/{org}", &[""], &mut q, |_| None).is_none());
+        // Braces that aren't placeholders are left alone
+        let url = expand_template("https://example.com/{a-b}/{0}", &["x"], &mut Vec::new(), |_| None);
+        assert_eq!(url.as_deref(), Some("https://example.com/{a-b}/x"));
+    }
+
+    #[test]
+    fn variables_and_arguments_are_filled_in_one_pass() {
+        let collections = [collection(&[("u", "https://u.example/{user}/{0}")])];
+        let settings = GoSettings::default();
+        let resolver = Resolver {
+            collections: &collections,
+            rules: &[],
+            settings: &settings,
+            variables: VariableContext { now: 0, user: Some("{0} a&b#c".to_string()) },
+        };
+        let resolved = resolver.resolve_request("u/seg").ok().map(|r| r.url);
+        assert_eq!(resolved.as_deref(), Some("https://u.example/%7B0%7D%20a%26b%23c/seg"));
+
+        // Without a value, the variable is an ordinary named placeholder
+        let resolver = Resolver { variables: VariableContext { now: 0, user: None }, ..resolver };
+        let resolved = resolver.resolve_request("u/seg?user=sam").ok().map(|r| r.url);
+        assert_eq!(resolved.as_deref(), Some("https://u.example/sam/seg"));
+    }
+
+    #[test]
+    fn template_fallback_keeps_a_usable_prefix() {
+        assert_eq!(template_fallback("https://github.com/{org}/{repo}", ""), "https://github.com/");
+        assert_eq!(template_fallback("https://example.com/search?q={q}", ""), "https://example.com/");
+        assert_eq!(template_fallback("https://jira.example.com/browse/PROJ-{0}", ""), "https://jira.example.com/browse/");
+        assert_eq!(template_fallback("/docs/{0}", ""), "/docs/");
+        assert_eq!(template_fallback("https://github.com/{0}", " https://fallback.example "), "https://fallback.example");
+    }
+
+    #[test]
+    fn template_fallback_without_a_host_goes_home() {
+        assert_eq!(template_fallback("https://{0}.atlassian.net/", ""), "/");
+        assert_eq!(template_fallback("https://jira-{0}.example.com/", ""), "/");
+        assert_eq!(template_fallback("{0}", ""), "/");
+    }
+
+    #[test]
+    fn merge_query_prefers_the_request() {
+        let q = query(&[("a", "9"), ("c", "x y")]);
+        assert_eq!(merge_query("https://x.example/?a=1&b=2&a=3#top", &q, None), "https://x.example/?a=9&b=2&c=x%20y#top");
+        assert_eq!(merge_query("https://x.example/p#top", &[], Some("end")), "https://x.example/p#end");
+        // Bare flags on the target survive; repeated request keys are all kept
+        let q = query(&[("tag", "a"), ("tag", "b")]);
+        assert_eq!(merge_query("https://x.example/?raw", &q, None), "https://x.example/?raw&tag=a&tag=b");
+    }
+
+    #[test]
+    fn smart_append_and_query_passthrough() {
+        let collections = [collection(&[("gh", "https://github.com"), ("docs", "https://docs.example/")])];
+        assert_eq!(resolve(&collections, "gh/rust-lang/rust").ok().as_deref(), Some("https://github.com/rust-lang/rust"));
+        assert_eq!(resolve(&collections, "docs/a?x=1").ok().as_deref(), Some("https://docs.example/a?x=1"));
+        assert!(matches!(resolve(&collections, "nope"), Err(ResolveError::NotFound)));
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_as_one() {
+        assert_eq!(edit_distance("gtihub", "github"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn suggest_ranks_by_distance_then_prefix() {
+        let keys: Vec<String> = ["github", "gitlab", "jira", "jenkins", "zoom"].iter().map(|k| k.to_string()).collect();
+
+        let found = suggest("gtihub", keys.iter());
+        assert_eq!(found.first().map(|s| s.key.as_str()), Some("github"));
+        assert!(!found.iter().any(|s| s.key == "zoom"));
+
+        // A prefix counts even when the distance is too large
+        let found = suggest("jen", keys.iter());
+        assert!(found.iter().any(|s| s.key == "jenkins"));
+
+        let found = suggest("gtihub", keys.iter());
+        assert_eq!(auto_redirect_target("gtihub", &found).map(|s| s.key.as_str()), Some("github"));
+        // Two aliases one edit away: ask instead of guessing
+        let found = suggest("gitlub", keys.iter());
+        assert!(auto_redirect_target("gitlub", &found).is_none());
+        // Too short to be sure
+        let found = suggest("jr", keys.iter());
+        assert!(auto_redirect_target("jr", &found).is_none());
+    }
+
+    #[test]
+    fn chains_follow_go_targets() {
+        let collections = [collection(&[("a", "go:b/x"), ("b", "https://b.example/"), ("s", "go:b?q=1")])];
+        assert_eq!(resolve(&collections, "a").ok().as_deref(), Some("https://b.example/x"));
+        // The request's parameters win over the chained target's
+        assert_eq!(resolve(&collections, "s?q=2").ok().as_deref(), Some("https://b.example/?q=2"));
+    }
+
+    #[test]
+    fn chain_cycles_are_reported() {
+        let collections = [collection(&[("a", "go:b"), ("b", "go:a")])];
+        match resolve(&collections, "a") {
+            Err(ResolveError::Cycle(chain)) => assert_eq!(chain, ["a", "b", "a"]),
+            _ => panic!("expected a cycle"),
+        }
+    }
+
+    #[test]
+    fn chains_are_limited_to_max_depth() {
+        // c0 -> c1 -> ... -> cN -> https://end.example
+        let chain = |hops: usize| {
+            let urls: Vec<(String, String)> = (0..=hops)
+                .map(|i| {
+                    let url = if i == hops { "https://end.example".to_string() } else { format!("go:c{}", i + 1) };
+                    (format!("c{}", i), url)
+                })
+                .collect();
+            let pairs: Vec<(&str, &str)> = urls.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            [collection(&pairs)]
+        };
+
+        assert_eq!(resolve(&chain(MAX_CHAIN_DEPTH), "c0").ok().as_deref(), Some("https://end.example"));
+        match resolve(&chain(MAX_CHAIN_DEPTH + 1), "c0") {
+            Err(ResolveError::TooDeep(chain)) => assert_eq!(chain.len(), MAX_CHAIN_DEPTH + 2),
+            _ => panic!("expected the chain to be too deep"),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use actix_web::{
+    post,
+    web::{Data, Form},
+    HttpResponse, Responder,
+};
+use htmlescape::encode_minimal;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, sync::Arc};
+
+use crate::app_state::AppState;
+use crate::config::files;
+use crate::resolve::encode_capture;
+
+// A rule-based shortcut, checked by `go` after exact matches and before smart append.
+// `regex` rules match the whole requested path (e.g. `^pr/(\d+)$`); `prefix` rules match
+// paths starting with `pattern` and capture the rest as `$1`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ShortcutRule {
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    pub pattern: String,
+    pub target: String,
+}
+
+fn default_kind() -> String {
+    "regex".to_string()
+}
+
+// A rule together with its compiled regex. Rules are only ever stored compiled, so a
+// pattern that fails to compile can't reach the routing code.
+pub struct CompiledRule {
+    pub rule: ShortcutRule,
+    regex: Regex,
+}
+
+impl CompiledRule {
+    pub fn compile(rule: ShortcutRule) -> Result<Self, String> {
+        if rule.pattern.is_empty() {
+            return Err("Pattern cannot be empty.".to_string());
+        }
+        if rule.target.trim().is_empty() {
+            return Err("Target URL cannot be empty.".to_string());
+        }
+        let source = match rule.kind.as_str() {
+            "regex" => rule.pattern.clone(),
+            // A prefix is matched literally, with everything after it as the first capture
+            "prefix" => format!("^{}(.*)$", regex::escape(&rule.pattern)),
+            other => return Err(format!("Unknown rule kind '{}'.", other)),
+        };
+        let regex = Regex::new(&source).map_err(|e| format!("Invalid pattern: {}", e))?;
+        Ok(CompiledRule { rule, regex })
+    }
+
+    // Builds the destination for `path` if this rule matches it. Captured text is
+    // percent-encoded (keeping `/`) before being substituted into the target.
+    pub fn apply(&self, path: &str) -> Option<String> {
+        let caps = self.regex.captures(path)?;
+        let mut encoded = String::new();
+        let mut last = 0;
+        let target = &self.rule.target;
+
+        // Walk `$1` / `${name}` references ourselves so each value can be encoded
+        while let Some(offset) = target[last..].find('$') {
+            let start = last + offset;
+            encoded.push_str(&target[last..start]);
+            let rest = &target[start + 1..];
+            let (name, consumed) = if let Some(inner) = rest.strip_prefix('{') {
+                match inner.find('}') {
+                    Some(end) => (&inner[..end], end + 2),
+                    None => ("", 0),
+                }
+            } else {
+                let len = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+                (&rest[..len], len)
+            };
+
+            if name.is_empty() {
+                // A lone `$` (or `$$`) is kept as a literal dollar sign
+                encoded.push('$');
+                last = start + 1 + usize::from(rest.starts_with('$'));
+                continue;
+            }
+
+            let value = match name.parse::<usize>() {
+                Ok(i) => caps.get(i),
+                Err(_) => caps.name(name),
+            };
+            encoded.push_str(&encode_capture(value.map(|m| m.as_str()).unwrap_or("")));
+            last = start + 1 + consumed;
+        }
+        encoded.push_str(&target[last..]);
+        Some(encoded)
+    }
+}
+
+pub fn load_rules(path: &str) -> io::Result<Vec<CompiledRule>> {
+    let data = fs::read_to_string(path)?;
+    let rules: Vec<ShortcutRule> = serde_json::from_str(&data)?;
+
+    // Skip (but report) invalid rules instead of refusing to start
+    Ok(rules
+        .into_iter()
+        .filter_map(|rule| {
+            let pattern = rule.pattern.clone();
+            CompiledRule::compile(rule)
+                .map_err(|e| eprintln!("Skipping rule '{}': {}", pattern, e))
+                .ok()
+        })
+        .collect())
+}
+
+fn save_rules(path: &str, rules: &[CompiledRule]) -> io::Result<()> {
+    let plain: Vec<&ShortcutRule> = rules.iter().map(|r| &r.rule).collect();
+    let data = serde_json::to_string_pretty(&plain)?;
+    fs::write(path, data)
+}
+
+// Finds the first rule (in list order) that matches `path`, returning its index and the destination
+pub fn match_rule(rules: &[CompiledRule], path: &str) -> Option<(usize, String)> {
+    rules
+        .iter()
+        .enumerate()
+        .find_map(|(i, r)| r.apply(path).map(|url| (i, url)))
+}
+
+#[derive(Deserialize)]
+pub struct AddRuleForm {
+    pub kind: String,
+    pub pattern: String,
+    pub target: String,
+}
+
+#[derive(Deserialize)]
+pub struct RuleIndexForm {
+    pub index: usize,
+    // "up" or "down" for /rules/move
+    pub direction: Option<String>,
+}
+
+// Handler for adding a rule to the end of the list
+#[post("/rules/add")]
+pub async fn rule_add(form: Form<AddRuleForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let rule = ShortcutRule {
+        kind: form.kind.trim().to_string(),
+        pattern: form.pattern.trim().to_string(),
+        target: form.target.trim().to_string(),
+    };
+
+    // Validate before anything is stored, so a bad pattern can't take down routing
+    let compiled = match CompiledRule::compile(rule) {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+
+    let mut rules = state.rules.lock().unwrap();
+    rules.push(compiled);
+    if let Err(e) = save_rules(&files().rules, &rules) {
+        eprintln!("Failed to save rules: {}", e);
+        return HttpResponse::InternalServerError().body("Failed to save rule.");
+    }
+
+    HttpResponse::Found()
+        .append_header(("Location", "/settings#rules"))
+        .finish()
+}
+
+#[post("/rules/delete")]
+pub async fn rule_delete(form: Form<RuleIndexForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let mut rules = state.rules.lock().unwrap();
+    if form.index < rules.len() {
+        rules.remove(form.index);
+        if let Err(e) = save_rules(&files().rules, &rules) {
+            eprintln!("Failed to save rules after deletion: {}", e);
+        }
+    }
+
+    HttpResponse::Found()
+        .append_header(("Location", "/settings#rules"))
+        .finish()
+}
+
+// Moves a rule one place up or down; earlier rules win
+#[post("/rules/move")]
+pub async fn rule_move(form: Form<RuleIndexForm>, state: Data<Arc<AppState>>) -> impl Responder {
+    let mut rules = state.rules.lock().unwrap();
+    let index = form.index;
+    let swap_with = match form.direction.as_deref() {
+        Some("up") if index > 0 => Some(index - 1),
+        Some("down") if index + 1 < rules.len() => Some(index + 1),
+        _ => None,
+    };
+
+    if let Some(other) = swap_with.filter(|_| index < rules.len()) {
+        rules.swap(index, other);
+        if let Err(e) = save_rules(&files().rules, &rules) {
+            eprintln!("Failed to save rules after reordering: {}", e);
+        }
+    }
+
+    HttpResponse::Found()
+        .append_header(("Location", "/settings#rules"))
+        .finish()
+}
+
+// Renders the rules section of the settings page: the ordered list, an add form and a test
+// box. `test_path` is the path entered in the test box, if any.
+pub fn render_rules_settings(rules: &[CompiledRule], test_path: Option<&str>) -> String {
+    let rows = rules
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            format!(
+                r#"<tr><td>{index}</td><td>{kind}</td><td><code>{pattern}</code></td><td class="url">{target}</td><td style="white-space: nowrap;">
+                    <form action="/rules/move" method="POST" style="display:inline; margin: 0;"><input type="hidden" name="index" value="{i}"><button type="submit" name="direction" value="up" title="Move up">&uarr;</button><button type="submit" name="direction" value="down" title="Move down">&darr;</button></form>
+                    <form action="/rules/delete" method="POST" style="display:inline; margin: 0;" onsubmit="return confirm('Delete this rule?');"><input type="hidden" name="index" value="{i}"><button type="submit" title="Delete rule">X</button></form>
+                </td></tr>"#,
+                index = i + 1,
+                i = i,
+                kind = encode_minimal(&r.rule.kind),
+                pattern = encode_minimal(&r.rule.pattern),
+                target = encode_minimal(&r.rule.target),
+            )
+        })
+        .collect::<String>();
+
+    let table = if rows.is_empty() {
+        "<p>No rules yet.</p>".to_string()
+    } else {
+        format!(
+            "<table class=\"grid\"><thead><tr><th>#</th><th>Kind</th><th>Pattern</th><th>Target</th><th></th></tr></thead><tbody>{}</tbody></table>",
+            rows
+        )
+    };
+
+    let test_result = match test_path {
+        None => String::new(),
+        Some(path) => match match_rule(rules, path) {
+            Some((i, url)) => format!(
+                "<p>Rule {} (<code>{}</code>) matches <code>{}</code> and redirects to <code>{}</code>.</p>",
+                i + 1,
+                encode_minimal(&rules[i].rule.pattern),
+                encode_minimal(path),
+                encode_minimal(&url)
+            ),
+            None => format!("<p>No rule matches <code>{}</code>.</p>", encode_minimal(path)),
+        },
+    };
+
+    format!(
+        r#"
+    <div class="settings-form" id="rules">
+        <h1>Rule Shortcuts</h1>
+        <p>Checked in order after exact shortcut matches and before smart append. Use <code>$1</code> or <code>${{name}}</code> in the target for captured text; prefix rules capture everything after the prefix as <code>$1</code>.</p>
+        {table}
+
+        <form action="/rules/add" method="POST" style="margin: 10px 0;">
+            <div class="settings-grid">
+                <div>
+                    <label for="rule_kind">Kind:</label>
+                    <select id="rule_kind" name="kind">
+                        <option value="regex">Regex</option>
+                        <option value="prefix">Prefix</option>
+                    </select>
+                </div>
+                <div>
+                    <label for="rule_pattern">Pattern:</label>
+                    <input type="text" id="rule_pattern" name="pattern" placeholder="e.g., ^pr/(\d+)$ or t/" required>
+                </div>
+                <div>
+                    <label for="rule_target">Target URL:</label>
+                    <input type="text" id="rule_target" name="target" placeholder="e.g., https://github.com/org/repo/pull/$1" required>
+                </div>
+            </div>
+            <div class="theme-action-buttons">
+                <button type="submit" class="form-submit-btn">Add Rule</button>
+            </div>
+        </form>
+
+        <form action="/settings#rules" method="GET" style="margin: 10px 0;">
+            <div class="settings-grid">
+                <div>
+                    <label for="rule_test">Test a path:</label>
+                    <input type="text" id="rule_test" name="rule_test" value="{test_value}" placeholder="e.g., pr/42">
+                </div>
+            </div>
+            <div class="theme-action-buttons">
+                <button type="submit" class="form-submit-btn">Test</button>
+            </div>
+        </form>
+        {test_result}
+    </div>
+"#,
+        table = table,
+        test_value = encode_minimal(test_path.unwrap_or("")),
+        test_result = test_result,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(kind: &str, pattern: &str, target: &str) -> CompiledRule {
+        let rule = ShortcutRule { kind: kind.to_string(), pattern: pattern.to_string(), target: target.to_string() };
+        CompiledRule::compile(rule).unwrap()
+    }
+
+    #[test]
+    fn regex_rules_substitute_numbered_and_named_captures() {
+        let pr = rule("regex", r"^pr/(\d+)$", "https://github.com/org/repo/pull/$1");
+        assert_eq!(pr.apply("pr/42").as_deref(), Some("https://github.com/org/repo/pull/42"));
+        assert!(pr.apply("pr/abc").is_none());
+        assert!(pr.apply("x/pr/42").is_none());
+
+        let named = rule("regex", r"^(?P<org>[^/]+)/issues/(?P<id>\d+)$", "https://github.com/${org}/x/issues/${id}");
+        assert_eq!(named.apply("rust-lang/issues/7").as_deref(), Some("https://github.com/rust-lang/x/issues/7"));
+    }
+
+    #[test]
+    fn prefix_rules_match_literally_and_capture_the_rest() {
+        let t = rule("prefix", "t.", "https://tickets.example/$1");
+        assert_eq!(t.apply("t.ABC-1/notes").as_deref(), Some("https://tickets.example/ABC-1/notes"));
+        // The dot is not a wildcard
+        assert!(t.apply("tx/ABC").is_none());
+    }
+
+    #[test]
+    fn captures_are_encoded_and_dollars_kept() {
+        let search = rule("prefix", "s/", "https://search.example/?q=$1&price=$$5");
+        assert_eq!(search.apply("s/a b&c").as_deref(), Some("https://search.example/?q=a%20b%26c&price=$5"));
+        // An unknown group is empty rather than an error
+        let missing = rule("regex", "^m/(.*)$", "https://m.example/$2");
+        assert_eq!(missing.apply("m/x").as_deref(), Some("https://m.example/"));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = vec![
+            rule("regex", r"^pr/(\d+)$", "https://github.com/org/repo/pull/$1"),
+            rule("prefix", "pr/", "https://github.com/org/repo/pulls?q=$1"),
+        ];
+        assert_eq!(match_rule(&rules, "pr/42"), Some((0, "https://github.com/org/repo/pull/42".to_string())));
+        assert_eq!(match_rule(&rules, "pr/open"), Some((1, "https://github.com/org/repo/pulls?q=open".to_string())));
+        assert_eq!(match_rule(&rules, "issues/1"), None);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let compile = |kind: &str, pattern: &str, target: &str| {
+            CompiledRule::compile(ShortcutRule { kind: kind.to_string(), pattern: pattern.to_string(), target: target.to_string() })
+        };
+        assert!(compile("regex", "(", "https://x").is_err());
+        assert!(compile("regex", "", "https://x").is_err());
+        assert!(compile("regex", "^a$", " ").is_err());
+        assert!(compile("glob", "a*", "https://x").is_err());
+    }
+}
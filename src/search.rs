@@ -0,0 +1,91 @@
+use actix_web::{get, web::{Data, Query}, HttpRequest, HttpResponse, Responder};
+use htmlescape::encode_minimal;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::app_state::AppState;
+use crate::hidden;
+use crate::resolve::{self, encode_capture, Resolver};
+use crate::stats;
+use crate::variables::VariableContext;
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+// Handler for GET /opensearch.xml
+// Lets browsers register the service as a search engine, so `go gh rust-lang` typed into
+// the address bar ends up at /search?q=gh+rust-lang.
+#[get("/opensearch.xml")]
+pub async fn opensearch_get(req: HttpRequest) -> impl Responder {
+    let info = req.connection_info();
+    let base = format!("{}://{}", info.scheme(), info.host());
+
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/" xmlns:moz="http://www.mozilla.org/2006/browser/search/">
+  <ShortName>go</ShortName>
+  <Description>Jump to a go shortcut</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <Url type="text/html" method="get" template="{base}/search?q={{searchTerms}}"/>
+  <moz:SearchForm>{base}/</moz:SearchForm>
+</OpenSearchDescription>
+"#,
+        base = encode_minimal(&base)
+    );
+
+    HttpResponse::Ok()
+        .content_type("application/opensearchdescription+xml; charset=utf-8")
+        .body(xml)
+}
+
+// Handler for GET /search?q=alias arg1 arg2
+// The first word is resolved as an alias through the same logic as `go`; the other words
+// become path segments (`{0}`, `{1}` in templates, appended for plain shortcuts), and the
+// whole remainder is also available to templates as `{q}`.
+#[get("/search")]
+pub async fn search_get(req: HttpRequest, query: Query<SearchQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    let words: Vec<&str> = query.q.split_whitespace().collect();
+    let Some((alias, args)) = words.split_first() else {
+        return HttpResponse::Found().append_header(("Location", "/")).finish();
+    };
+
+    let path = std::iter::once(*alias).chain(args.iter().copied()).collect::<Vec<_>>().join("/");
+    let mut params = vec![("q".to_string(), args.join(" "))];
+
+    let collections = state.collections.lock().unwrap();
+    let rules = state.rules.lock().unwrap();
+    let go_settings = state.go_settings.lock().unwrap();
+
+    let resolver = Resolver {
+        collections: &collections,
+        rules: &rules,
+        settings: &go_settings,
+        variables: VariableContext::from_request(&req),
+    };
+    match resolver.resolve(&path, &mut params) {
+        Ok(resolved) => {
+            if resolved.locked && !hidden::is_unlocked(&req, &state) {
+                return hidden::unlock_redirect(req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/"));
+            }
+            // Done with the shortcuts; don't hold their locks while counting the hit
+            drop(go_settings);
+            drop(rules);
+            drop(collections);
+            if let Some(alias) = &resolved.alias {
+                stats::record_hit(&state, alias);
+            }
+            // `q` only exists for templates; don't leak it onto other destinations
+            params.retain(|(k, _)| k != "q");
+            HttpResponse::Found()
+                .append_header(("Location", resolve::merge_query(&resolved.url, &params, None)))
+                .finish()
+        }
+        // Let the regular route render the 404 page (with suggestions) or the chain error
+        Err(_) => HttpResponse::Found()
+            .append_header(("Location", format!("/{}", encode_capture(&path))))
+            .finish(),
+    }
+}
@@ -3,6 +3,7 @@ use std::{collections::HashMap, sync::Arc, fs, io};
 use serde::{Deserialize, Serialize};
 use crate::app_state::AppState;
 use crate::base_page::render_base_page;
+use crate::config::files;
 use crate::sql::{
     DbConnection, SqlForm, AddConnForm,
     find_connection, render_table,
@@ -10,8 +11,6 @@ use crate::sql::{
 };
 
 // --- NEW: Saved Query Structures and Persistence ---
-const QUERIES_FILE: &str = "saved_queries.json";
-
 #[derive(Serialize, Deserialize, Clone)]
 struct SavedQuery {
     name: String,
@@ -32,7 +31,7 @@ struct DeleteQueryForm {
 }
 
 fn load_queries() -> Vec<SavedQuery> {
-    fs::read_to_string(QUERIES_FILE)
+    fs::read_to_string(&files().queries)
         .ok()
         .and_then(|data| serde_json::from_str(&data).ok())
         .unwrap_or_default()
@@ -40,7 +39,7 @@ fn load_queries() -> Vec<SavedQuery> {
 
 fn save_queries(queries: &[SavedQuery]) -> io::Result<()> {
     let data = serde_json::to_string_pretty(queries)?;
-    fs::write(QUERIES_FILE, data)
+    fs::write(&files().queries, data)
 }
 
 // Helper to remove a query by name
@@ -274,7 +273,7 @@ pub async fn sql_run(form: Form<SqlForm>, state: Data<Arc<AppState>>) -> impl Re
         }
     };
 
-    let headers: Vec<String> = rows.get(0)
+    let headers: Vec<String> = rows.first()
         .map(|row| row.columns().iter().map(|col| col.name().to_string()).collect())
         .unwrap_or_default();
 
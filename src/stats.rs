@@ -0,0 +1,321 @@
+use actix_web::{get, web::Data, HttpResponse, Responder};
+use htmlescape::encode_minimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::app_state::{AppState, Shortcut, Theme};
+use crate::base_page::render_base_page;
+use crate::elements::shortcut::visible_shortcuts;
+use crate::clock::{current_ts, format_date, format_datetime, SECONDS_IN_DAY};
+use crate::config::files;
+use crate::health::{self, LinkHealth};
+
+// Shortcuts unused for this many days are listed as stale
+const STALE_AFTER_DAYS: u64 = 30;
+// How many days the histogram on /stats covers; older daily counts are dropped
+pub const HISTOGRAM_DAYS: u64 = 30;
+const TOP_LIMIT: usize = 20;
+// How often recorded hits are written out; a crash loses at most this much usage
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Set when the in-memory stats have changes that aren't saved yet
+static DIRTY: AtomicBool = AtomicBool::new(false);
+
+// Usage recorded for a single alias
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ShortcutStats {
+    pub hits: u64,
+    pub first_used: u64,
+    pub last_used: u64,
+    // Hits per day, keyed by YYYY-MM-DD
+    #[serde(default)]
+    pub daily: BTreeMap<String, u64>,
+}
+
+pub fn load_stats(path: &str) -> io::Result<HashMap<String, ShortcutStats>> {
+    let data = fs::read_to_string(path)?;
+    let map: HashMap<String, ShortcutStats> = serde_json::from_str(&data)?;
+    Ok(map)
+}
+
+fn save_stats(path: &str, stats: &HashMap<String, ShortcutStats>) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(stats)?;
+    fs::write(path, data)
+}
+
+// Records a successful redirect through `alias`. Only memory is updated; the background
+// writer (see spawn_stats_writer) saves the file, so a redirect never waits on the disk.
+pub fn record_hit(state: &AppState, alias: &str) {
+    let now = current_ts();
+    let mut stats = state.stats.lock().unwrap();
+    let entry = stats.entry(alias.to_string()).or_default();
+    if entry.hits == 0 {
+        entry.first_used = now;
+    }
+    entry.hits += 1;
+    entry.last_used = now;
+    *entry.daily.entry(format_date(now)).or_insert(0) += 1;
+    DIRTY.store(true, Ordering::SeqCst);
+}
+
+// Moves the recorded usage of a renamed alias over to its new name, saving straight away
+// (the CLI edits shortcuts without a background writer).
+pub fn rename_alias(state: &AppState, old: &str, new: &str) {
+    let mut stats = state.stats.lock().unwrap();
+    if let Some(entry) = stats.remove(old) {
+        stats.insert(new.to_string(), entry);
+        DIRTY.store(true, Ordering::SeqCst);
+    }
+    drop(stats);
+    flush(state);
+}
+
+// Starts the background job that saves the recorded hits every SAVE_INTERVAL.
+pub fn spawn_stats_writer(state: Arc<AppState>) {
+    thread::spawn(move || loop {
+        thread::sleep(SAVE_INTERVAL);
+        flush(&state);
+    });
+}
+
+// Saves the stats if they changed since the last save, dropping daily counts older than the
+// histogram on the way. Also called on shutdown.
+pub fn flush(state: &AppState) {
+    if !DIRTY.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    let mut stats = state.stats.lock().unwrap();
+    prune_daily(&mut stats, current_ts());
+    if let Err(e) = save_stats(&files().stats, &stats) {
+        eprintln!("Failed to save shortcut stats: {}", e);
+        DIRTY.store(true, Ordering::SeqCst);
+    }
+}
+
+fn prune_daily(stats: &mut HashMap<String, ShortcutStats>, now: u64) {
+    let oldest = format_date(now.saturating_sub((HISTOGRAM_DAYS - 1) * SECONDS_IN_DAY));
+    for entry in stats.values_mut() {
+        entry.daily = entry.daily.split_off(&oldest);
+    }
+}
+
+// Handler for GET /stats
+#[get("/stats")]
+pub async fn stats_get(state: Data<Arc<AppState>>) -> impl Responder {
+    let collections = state.collections.lock().unwrap();
+    let stats = state.stats.lock().unwrap();
+    let current_theme = state.current_theme.lock().unwrap();
+    let health = state.health.lock().unwrap();
+
+    // Only report on *visible* shortcuts, the same set the home page lists
+    let visible = visible_shortcuts(&collections);
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_stats_page(&visible, &stats, &health, &current_theme))
+}
+
+fn render_stats_page(
+    shortcuts: &HashMap<String, Shortcut>,
+    stats: &HashMap<String, ShortcutStats>,
+    health: &HashMap<String, LinkHealth>,
+    current_theme: &Theme,
+) -> String {
+    let now = current_ts();
+
+    let mut used: Vec<(&String, &ShortcutStats)> = shortcuts
+        .keys()
+        .filter_map(|k| stats.get(k).filter(|s| s.hits > 0).map(|s| (k, s)))
+        .collect();
+
+    // Top: most hits first
+    used.sort_by(|a, b| b.1.hits.cmp(&a.1.hits).then(a.0.cmp(b.0)));
+    let top_rows = used
+        .iter()
+        .take(TOP_LIMIT)
+        .map(|(k, s)| stats_row(k, shortcuts, s))
+        .collect::<String>();
+
+    // Stale: used before, but not recently; oldest first
+    let stale_cutoff = now.saturating_sub(STALE_AFTER_DAYS * SECONDS_IN_DAY);
+    let mut stale: Vec<_> = used.iter().filter(|(_, s)| s.last_used < stale_cutoff).collect();
+    stale.sort_by_key(|(_, s)| s.last_used);
+    let stale_rows = stale
+        .iter()
+        .map(|(k, s)| stats_row(k, shortcuts, s))
+        .collect::<String>();
+
+    // Never used: no recorded hits at all
+    let mut never: Vec<&String> = shortcuts
+        .keys()
+        .filter(|k| stats.get(*k).is_none_or(|s| s.hits == 0))
+        .collect();
+    never.sort();
+    let never_rows = never
+        .iter()
+        .map(|k| {
+            format!(
+                "<tr><td><a href=\"/{0}\">{0}</a></td><td class=\"url\">{1}</td></tr>",
+                encode_minimal(k),
+                encode_minimal(&shortcuts[*k].url)
+            )
+        })
+        .collect::<String>();
+
+    // Daily histogram across all aliases
+    let mut per_day: BTreeMap<String, u64> = BTreeMap::new();
+    for day in 0..HISTOGRAM_DAYS {
+        per_day.insert(format_date(now - day * SECONDS_IN_DAY), 0);
+    }
+    for s in stats.values() {
+        for (day, count) in &s.daily {
+            if let Some(total) = per_day.get_mut(day) {
+                *total += count;
+            }
+        }
+    }
+    let max_day = per_day.values().copied().max().unwrap_or(0).max(1);
+    let histogram_rows = per_day
+        .iter()
+        .rev()
+        .map(|(day, count)| {
+            format!(
+                "<tr><td>{}</td><td><div class=\"stats-bar\" style=\"width: {}%;\"></div></td><td>{}</td></tr>",
+                day,
+                count * 100 / max_day,
+                count
+            )
+        })
+        .collect::<String>();
+
+    // Link health: broken destinations first, then redirects, each listing the aliases using them
+    let mut by_url: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (key, shortcut) in shortcuts {
+        by_url.entry(shortcut.url.as_str()).or_default().push(key.as_str());
+    }
+    let mut flagged: Vec<(&str, &LinkHealth)> = by_url
+        .keys()
+        .filter_map(|url| health.get(*url).map(|h| (*url, h)))
+        .filter(|(_, h)| h.is_broken() || h.is_redirect())
+        .collect();
+    flagged.sort_by_key(|(url, h)| (!h.is_broken(), *url));
+    let health_rows = flagged
+        .iter()
+        .map(|(url, h)| {
+            let mut keys = by_url[url].clone();
+            keys.sort();
+            format!(
+                "<tr><td>{}</td><td class=\"url\">{}</td><td>{}</td><td>{}</td><td>{} ms</td><td>{}</td></tr>",
+                keys.iter().map(|k| format!("<a href=\"/{0}\">{0}</a>", encode_minimal(k))).collect::<Vec<_>>().join(", "),
+                encode_minimal(url),
+                if h.is_broken() { "Broken" } else { "Redirects" },
+                encode_minimal(&h.summary()),
+                h.latency_ms,
+                format_datetime(h.checked_at)
+            )
+        })
+        .collect::<String>();
+
+    let style = r#"
+<style>
+    .stats-page { padding: 0 10px 20px 10px; overflow-y: auto; }
+    .stats-bar { height: 12px; background: var(--link-color); border-radius: 2px; min-width: 1px; }
+    .stats-empty { margin: 10px; opacity: 0.7; }
+</style>
+"#;
+
+    let stats_header = "<tr><th>Shortcut</th><th>Destination URL</th><th>Hits</th><th>First Used</th><th>Last Used</th></tr>";
+    let table = |header: &str, rows: String, empty: &str| -> String {
+        if rows.is_empty() {
+            format!("<p class=\"stats-empty\">{}</p>", empty)
+        } else {
+            format!("<table class=\"grid\"><thead>{}</thead><tbody>{}</tbody></table>", header, rows)
+        }
+    };
+
+    let content = format!(
+        r#"
+    {style}
+    <div class="stats-page">
+        <h1>Shortcut Usage</h1>
+
+        <h2>Top Shortcuts</h2>
+        {top}
+
+        <h2>Stale Shortcuts (unused for {stale_days}+ days)</h2>
+        {stale}
+
+        <h2>Never Used</h2>
+        {never}
+
+        <h2 id="health">Link Health</h2>
+        <form action="/health/check" method="POST" style="margin: 10px;">
+            {last_check}
+            <button type="submit" class="form-submit-btn">Check Links Now</button>
+        </form>
+        {health}
+
+        <h2>Daily Hits (last {histogram_days} days)</h2>
+        <table class="grid"><thead><tr><th>Day</th><th style="width: 70%;">Hits</th><th></th></tr></thead><tbody>{histogram}</tbody></table>
+    </div>
+    "#,
+        style = style,
+        top = table(stats_header, top_rows, "No shortcut has been used yet."),
+        stale_days = STALE_AFTER_DAYS,
+        stale = table(stats_header, stale_rows, "No stale shortcuts."),
+        never = table("<tr><th>Shortcut</th><th>Destination URL</th></tr>", never_rows, "Every shortcut has been used."),
+        last_check = health::last_run_description(),
+        health = table(
+            "<tr><th>Shortcut</th><th>Destination URL</th><th>Status</th><th>Result</th><th>Latency</th><th>Checked</th></tr>",
+            health_rows,
+            "No broken or redirected links."
+        ),
+        histogram_days = HISTOGRAM_DAYS,
+        histogram = histogram_rows,
+    );
+
+    render_base_page("Shortcut Stats", &content, current_theme)
+}
+
+fn stats_row(key: &str, shortcuts: &HashMap<String, Shortcut>, s: &ShortcutStats) -> String {
+    format!(
+        "<tr><td><a href=\"/{0}\">{0}</a></td><td class=\"url\">{1}</td><td>{2}</td><td>{3}</td><td>{4}</td></tr>",
+        encode_minimal(key),
+        encode_minimal(shortcuts.get(key).map(|sc| sc.url.as_str()).unwrap_or("")),
+        s.hits,
+        format_datetime(s.first_used),
+        format_datetime(s.last_used)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prunes_daily_counts_older_than_the_histogram() {
+        let now = 1792195200; // 2026-10-17
+        let mut entry = ShortcutStats { hits: 4, ..Default::default() };
+        for day in ["2026-09-17", "2026-09-18", "2026-10-01", "2026-10-17"] {
+            entry.daily.insert(day.to_string(), 1);
+        }
+        let mut stats = HashMap::from([("gh".to_string(), entry)]);
+
+        prune_daily(&mut stats, now);
+        let days: Vec<&str> = stats["gh"].daily.keys().map(String::as_str).collect();
+        // The 30 days up to and including today, as shown on /stats
+        assert_eq!(days, ["2026-09-18", "2026-10-01", "2026-10-17"]);
+        assert_eq!(stats["gh"].hits, 4);
+    }
+}
@@ -0,0 +1,100 @@
+// Variables for shortcut URLs (`{today}`, `{yyyy}`, `{week}`, `{user}`, ...), filled in at
+// redirect time together with the template placeholders (see resolve.rs), so
+// `standup -> https://docs.example/notes/{today}` always opens today's page.
+//
+// New built-ins are added to BUILTIN. Values from the config file's `variables` and GO_VAR_*
+// environment variables are available too (see config.rs), but can't replace a built-in.
+// Values are returned as they are; the resolver percent-encodes them. A variable without a value
+// (`{user}` without a login proxy) is filled from the query string like any named placeholder.
+
+use actix_web::HttpRequest;
+
+use crate::clock::{civil_from_days, current_ts, format_date, iso_week, SECONDS_IN_DAY};
+use crate::config::config;
+
+// What a variable can draw on when a path is resolved
+pub struct VariableContext {
+    pub now: u64,
+    // Signed-in user, from the header a login proxy sets (see `user_header`)
+    pub user: Option<String>,
+}
+
+impl Default for VariableContext {
+    fn default() -> Self {
+        VariableContext { now: current_ts(), user: None }
+    }
+}
+
+impl VariableContext {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        // Only trusted when a login proxy is configured to set it; clients could send it too
+        let header = config().user_header.as_str();
+        let user = Some(header)
+            .filter(|h| !h.is_empty())
+            .and_then(|h| req.headers().get(h))
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(String::from);
+        VariableContext { user, ..Default::default() }
+    }
+}
+
+pub struct Variable {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value: fn(&VariableContext) -> Option<String>,
+}
+
+fn date_part(ts: u64) -> (i64, u32, u32) {
+    civil_from_days((ts / SECONDS_IN_DAY) as i64)
+}
+
+// Dates are UTC, like everywhere else in the service
+pub static BUILTIN: &[Variable] = &[
+    Variable { name: "today", description: "today's date, e.g. 2024-05-01", value: |c| Some(format_date(c.now)) },
+    Variable {
+        name: "yesterday",
+        description: "yesterday's date",
+        value: |c| Some(format_date(c.now.saturating_sub(SECONDS_IN_DAY))),
+    },
+    Variable { name: "tomorrow", description: "tomorrow's date", value: |c| Some(format_date(c.now + SECONDS_IN_DAY)) },
+    Variable { name: "yyyy", description: "the year, e.g. 2024", value: |c| Some(format!("{:04}", date_part(c.now).0)) },
+    Variable { name: "mm", description: "the month, 01 to 12", value: |c| Some(format!("{:02}", date_part(c.now).1)) },
+    Variable { name: "dd", description: "the day of the month, 01 to 31", value: |c| Some(format!("{:02}", date_part(c.now).2)) },
+    Variable { name: "week", description: "the ISO week number, 01 to 53", value: |c| Some(format!("{:02}", iso_week(c.now).1)) },
+    Variable {
+        name: "weekyear",
+        description: "the year the ISO week belongs to, for {weekyear}-W{week}",
+        value: |c| Some(format!("{:04}", iso_week(c.now).0)),
+    },
+    Variable {
+        name: "user",
+        description: "the signed-in user, from the login proxy",
+        value: |c| c.user.clone(),
+    },
+];
+
+// The value of `{name}`, or None if it isn't a variable or has no value right now
+pub fn lookup(name: &str, context: &VariableContext) -> Option<String> {
+    match BUILTIN.iter().find(|v| v.name == name) {
+        Some(variable) => (variable.value)(context),
+        None => config().variables.get(name).cloned(),
+    }
+}
+
+// Every variable name with a short description, built-ins first, for help texts
+pub fn list() -> Vec<(String, String)> {
+    let mut configured: Vec<&String> = config()
+        .variables
+        .keys()
+        .filter(|name| !BUILTIN.iter().any(|v| v.name == name.as_str()))
+        .collect();
+    configured.sort();
+
+    BUILTIN
+        .iter()
+        .map(|v| (v.name.to_string(), v.description.to_string()))
+        .chain(configured.into_iter().map(|name| (name.clone(), "set in the configuration".to_string())))
+        .collect()
+}
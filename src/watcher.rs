@@ -0,0 +1,51 @@
+use std::{sync::Arc, thread, time::Duration};
+
+use crate::app_state::AppState;
+use crate::elements::shortcut::{file_modified, log_collisions, read_collection};
+
+// How often the shortcut files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Starts a background thread that polls each collection's file and reloads it when its
+// modification time changes.
+pub fn spawn_shortcut_watcher(state: Arc<AppState>) {
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        reload_changed(&state);
+    });
+}
+
+fn reload_changed(state: &AppState) {
+    let mut collections = state.collections.lock().unwrap();
+    let mut reloaded = false;
+
+    for collection in collections.iter_mut() {
+        let modified = file_modified(&collection.file);
+        // A missing file (e.g. halfway through a sync) keeps the shortcuts we have
+        if modified.is_none() || modified == collection.modified {
+            continue;
+        }
+        collection.modified = modified;
+
+        match read_collection(&collection.file, collection.directory) {
+            Ok((shortcuts, sources)) => {
+                collection.load_error = None;
+                collection.sources = sources;
+                // Our own saves also bump the timestamp; only report real changes
+                if shortcuts != collection.shortcuts {
+                    eprintln!("Reloaded {} ({} shortcuts)", collection.file, shortcuts.len());
+                    collection.shortcuts = shortcuts;
+                    reloaded = true;
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to reload {}: {e}. Keeping the last good version.", collection.file);
+                collection.load_error = Some(e);
+            }
+        }
+    }
+
+    if reloaded {
+        log_collisions(&collections);
+    }
+}